@@ -5,13 +5,17 @@ use syn::{punctuated::Punctuated, DeriveInput, Token};
 use crate::code::Code;
 use crate::diagnostic_arg::DiagnosticArg;
 use crate::diagnostic_source::DiagnosticSource;
+use crate::fatal::Fatal;
 use crate::forward::{Forward, WhichFn};
 use crate::help::Help;
 use crate::label::Labels;
+use crate::long_message::LongMessage;
 use crate::related::Related;
 use crate::severity::Severity;
 use crate::source_code::SourceCode;
+use crate::title::Title;
 use crate::url::Url;
+use crate::utils::gen_all_variants_with;
 
 pub enum Diagnostic {
     Struct {
@@ -61,7 +65,10 @@ impl DiagnosticDefArgs {
 pub struct DiagnosticConcreteArgs {
     pub code: Option<Code>,
     pub severity: Option<Severity>,
+    pub fatal: Option<Fatal>,
     pub help: Option<Help>,
+    pub title: Option<Title>,
+    pub long_message: Option<LongMessage>,
     pub labels: Option<Labels>,
     pub source_code: Option<SourceCode>,
     pub url: Option<Url>,
@@ -80,8 +87,11 @@ impl DiagnosticConcreteArgs {
         Ok(DiagnosticConcreteArgs {
             code: None,
             help,
+            title: None,
+            long_message: None,
             related,
             severity: None,
+            fatal: None,
             labels,
             url: None,
             forward: None,
@@ -128,6 +138,15 @@ impl DiagnosticConcreteArgs {
                     }
                     self.severity = Some(sev);
                 }
+                DiagnosticArg::Fatal(fatal) => {
+                    if self.fatal.is_some() {
+                        errors.push(syn::Error::new_spanned(
+                            attr,
+                            "fatal/non_fatal has already been specified",
+                        ));
+                    }
+                    self.fatal = Some(fatal);
+                }
                 DiagnosticArg::Help(hl) => {
                     if self.help.is_some() {
                         errors.push(syn::Error::new_spanned(
@@ -137,6 +156,24 @@ impl DiagnosticConcreteArgs {
                     }
                     self.help = Some(hl);
                 }
+                DiagnosticArg::Title(title) => {
+                    if self.title.is_some() {
+                        errors.push(syn::Error::new_spanned(
+                            attr,
+                            "title has already been specified",
+                        ));
+                    }
+                    self.title = Some(title);
+                }
+                DiagnosticArg::LongMessage(long_message) => {
+                    if self.long_message.is_some() {
+                        errors.push(syn::Error::new_spanned(
+                            attr,
+                            "long_message has already been specified",
+                        ));
+                    }
+                    self.long_message = Some(long_message);
+                }
                 DiagnosticArg::Url(u) => {
                     if self.url.is_some() {
                         errors.push(syn::Error::new_spanned(
@@ -282,10 +319,17 @@ impl Diagnostic {
                     DiagnosticDefArgs::Transparent(forward) => {
                         let code_method = forward.gen_struct_method(WhichFn::Code);
                         let help_method = forward.gen_struct_method(WhichFn::Help);
+                        let title_method = forward.gen_struct_method(WhichFn::Title);
+                        let long_message_method = forward.gen_struct_method(WhichFn::LongMessage);
                         let url_method = forward.gen_struct_method(WhichFn::Url);
                         let labels_method = forward.gen_struct_method(WhichFn::Labels);
+                        let context_snippets_method =
+                            forward.gen_struct_method(WhichFn::ContextSnippets);
                         let source_code_method = forward.gen_struct_method(WhichFn::SourceCode);
+                        let source_code_owned_method =
+                            forward.gen_struct_method(WhichFn::SourceCodeOwned);
                         let severity_method = forward.gen_struct_method(WhichFn::Severity);
+                        let is_fatal_method = forward.gen_struct_method(WhichFn::IsFatal);
                         let related_method = forward.gen_struct_method(WhichFn::Related);
                         let diagnostic_source_method =
                             forward.gen_struct_method(WhichFn::DiagnosticSource);
@@ -294,10 +338,15 @@ impl Diagnostic {
                             impl #impl_generics miette::Diagnostic for #ident #ty_generics #where_clause {
                                 #code_method
                                 #help_method
+                                #title_method
+                                #long_message_method
                                 #url_method
                                 #labels_method
+                                #context_snippets_method
                                 #severity_method
+                                #is_fatal_method
                                 #source_code_method
+                                #source_code_owned_method
                                 #related_method
                                 #diagnostic_source_method
                             }
@@ -320,11 +369,28 @@ impl Diagnostic {
                             .as_ref()
                             .and_then(|x| x.gen_struct(fields))
                             .or_else(|| forward(WhichFn::Help));
+                        let title_body = concrete
+                            .title
+                            .as_ref()
+                            .and_then(|x| x.gen_struct(fields))
+                            .or_else(|| forward(WhichFn::Title));
+                        let long_message_body = concrete
+                            .long_message
+                            .as_ref()
+                            .and_then(|x| x.gen_struct(fields))
+                            .or_else(|| forward(WhichFn::LongMessage));
                         let sev_body = concrete
                             .severity
                             .as_ref()
                             .and_then(|x| x.gen_struct())
                             .or_else(|| forward(WhichFn::Severity));
+                        // A local severity takes precedence over a forwarded
+                        // `is_fatal`, same as it does for `severity`.
+                        let fatal_body = match (&concrete.fatal, &concrete.severity) {
+                            (Some(fatal), _) => fatal.gen_struct(),
+                            (None, Some(_)) => None,
+                            (None, None) => forward(WhichFn::IsFatal),
+                        };
                         let rel_body = concrete
                             .related
                             .as_ref()
@@ -340,11 +406,18 @@ impl Diagnostic {
                             .as_ref()
                             .and_then(|x| x.gen_struct(fields))
                             .or_else(|| forward(WhichFn::Labels));
+                        let context_snippets_body = forward(WhichFn::ContextSnippets);
                         let src_body = concrete
                             .source_code
                             .as_ref()
                             .and_then(|x| x.gen_struct(fields))
                             .or_else(|| forward(WhichFn::SourceCode));
+                        // Source code of its own takes precedence over any
+                        // forwarded owned source code.
+                        let src_owned_body = match &concrete.source_code {
+                            Some(_) => None,
+                            None => forward(WhichFn::SourceCodeOwned),
+                        };
                         let diagnostic_source = concrete
                             .diagnostic_source
                             .as_ref()
@@ -354,11 +427,16 @@ impl Diagnostic {
                             impl #impl_generics miette::Diagnostic for #ident #ty_generics #where_clause {
                                 #code_body
                                 #help_body
+                                #title_body
+                                #long_message_body
                                 #sev_body
+                                #fatal_body
                                 #rel_body
                                 #url_body
                                 #labels_body
+                                #context_snippets_body
                                 #src_body
+                                #src_owned_body
                                 #diagnostic_source
                             }
                         }
@@ -373,9 +451,15 @@ impl Diagnostic {
                 let (impl_generics, ty_generics, where_clause) = &generics.split_for_impl();
                 let code_body = Code::gen_enum(variants);
                 let help_body = Help::gen_enum(variants);
+                let title_body = Title::gen_enum(variants);
+                let long_message_body = LongMessage::gen_enum(variants);
                 let sev_body = Severity::gen_enum(variants);
+                let fatal_body = Fatal::gen_enum(variants);
                 let labels_body = Labels::gen_enum(variants);
+                let context_snippets_body =
+                    gen_all_variants_with(variants, WhichFn::ContextSnippets, |_, _, _| None);
                 let src_body = SourceCode::gen_enum(variants);
+                let src_owned_body = SourceCode::gen_enum_owned(variants);
                 let rel_body = Related::gen_enum(variants);
                 let url_body = Url::gen_enum(ident, variants);
                 let diagnostic_source_body = DiagnosticSource::gen_enum(variants);
@@ -383,9 +467,14 @@ impl Diagnostic {
                     impl #impl_generics miette::Diagnostic for #ident #ty_generics #where_clause {
                         #code_body
                         #help_body
+                        #title_body
+                        #long_message_body
                         #sev_body
+                        #fatal_body
                         #labels_body
+                        #context_snippets_body
                         #src_body
+                        #src_owned_body
                         #rel_body
                         #url_body
                         #diagnostic_source_body
@@ -1,16 +1,22 @@
 use syn::parse::{Parse, ParseStream};
 
 use crate::code::Code;
+use crate::fatal::Fatal;
 use crate::forward::Forward;
 use crate::help::Help;
+use crate::long_message::LongMessage;
 use crate::severity::Severity;
+use crate::title::Title;
 use crate::url::Url;
 
 pub enum DiagnosticArg {
     Transparent,
     Code(Code),
     Severity(Severity),
+    Fatal(Fatal),
     Help(Help),
+    Title(Title),
+    LongMessage(LongMessage),
     Url(Url),
     Forward(Forward),
 }
@@ -28,9 +34,15 @@ impl Parse for DiagnosticArg {
             Ok(DiagnosticArg::Code(input.parse()?))
         } else if ident == "severity" {
             Ok(DiagnosticArg::Severity(input.parse()?))
+        } else if ident == "fatal" || ident == "non_fatal" {
+            Ok(DiagnosticArg::Fatal(input.parse()?))
         } else if ident == "help" {
             Ok(DiagnosticArg::Help(input.parse()?))
-        } else if ident == "url" {
+        } else if ident == "title" {
+            Ok(DiagnosticArg::Title(input.parse()?))
+        } else if ident == "long_message" {
+            Ok(DiagnosticArg::LongMessage(input.parse()?))
+        } else if ident == "url" || ident == "url_template" {
             Ok(DiagnosticArg::Url(input.parse()?))
         } else {
             Err(syn::Error::new(
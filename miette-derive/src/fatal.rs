@@ -0,0 +1,60 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::parse::{Parse, ParseStream};
+
+use crate::{
+    diagnostic::{DiagnosticConcreteArgs, DiagnosticDef},
+    forward::WhichFn,
+    utils::{gen_all_variants_with, gen_unused_pat},
+};
+
+pub struct Fatal(pub bool);
+
+impl Parse for Fatal {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let ident = input.parse::<syn::Ident>()?;
+        if ident == "fatal" {
+            Ok(Fatal(true))
+        } else if ident == "non_fatal" {
+            Ok(Fatal(false))
+        } else {
+            Err(syn::Error::new(
+                ident.span(),
+                "MIETTE BUG: not a fatal option",
+            ))
+        }
+    }
+}
+
+impl Fatal {
+    pub(crate) fn gen_enum(variants: &[DiagnosticDef]) -> Option<TokenStream> {
+        gen_all_variants_with(
+            variants,
+            WhichFn::IsFatal,
+            |ident,
+             fields,
+             DiagnosticConcreteArgs {
+                 fatal, severity, ..
+             }| {
+                // Without an explicit `fatal`/`non_fatal`, a variant's own
+                // severity decides, rather than whatever it forwards to.
+                let fatal = match (fatal, severity) {
+                    (Some(Fatal(fatal)), _) => *fatal,
+                    (None, Some(severity)) => severity.0 == "Error",
+                    (None, None) => return None,
+                };
+                let fields = gen_unused_pat(fields);
+                Some(quote! { Self::#ident #fields => #fatal, })
+            },
+        )
+    }
+
+    pub(crate) fn gen_struct(&self) -> Option<TokenStream> {
+        let fatal = self.0;
+        Some(quote! {
+            fn is_fatal(&self) -> bool {
+                #fatal
+            }
+        })
+    }
+}
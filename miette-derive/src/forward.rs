@@ -33,10 +33,15 @@ impl Parse for Forward {
 pub enum WhichFn {
     Code,
     Help,
+    Title,
+    LongMessage,
     Url,
     Severity,
+    IsFatal,
     Labels,
+    ContextSnippets,
     SourceCode,
+    SourceCodeOwned,
     Related,
     DiagnosticSource,
 }
@@ -46,10 +51,15 @@ impl WhichFn {
         match self {
             Self::Code => quote! { code() },
             Self::Help => quote! { help() },
+            Self::Title => quote! { title() },
+            Self::LongMessage => quote! { long_message() },
             Self::Url => quote! { url() },
             Self::Severity => quote! { severity() },
+            Self::IsFatal => quote! { is_fatal() },
             Self::Labels => quote! { labels() },
+            Self::ContextSnippets => quote! { context_snippets() },
             Self::SourceCode => quote! { source_code() },
+            Self::SourceCodeOwned => quote! { source_code_owned() },
             Self::Related => quote! { related() },
             Self::DiagnosticSource => quote! { diagnostic_source() },
         }
@@ -63,21 +73,36 @@ impl WhichFn {
             Self::Help => quote! {
                 fn help(& self) -> std::option::Option<std::boxed::Box<dyn std::fmt::Display + '_>>
             },
+            Self::Title => quote! {
+                fn title(& self) -> std::option::Option<std::boxed::Box<dyn std::fmt::Display + '_>>
+            },
+            Self::LongMessage => quote! {
+                fn long_message(& self) -> std::option::Option<std::boxed::Box<dyn std::fmt::Display + '_>>
+            },
             Self::Url => quote! {
                 fn url(& self) -> std::option::Option<std::boxed::Box<dyn std::fmt::Display + '_>>
             },
             Self::Severity => quote! {
                 fn severity(&self) -> std::option::Option<miette::Severity>
             },
+            Self::IsFatal => quote! {
+                fn is_fatal(&self) -> bool
+            },
             Self::Related => quote! {
                 fn related(&self) -> std::option::Option<std::boxed::Box<dyn std::iter::Iterator<Item = &dyn miette::Diagnostic> + '_>>
             },
             Self::Labels => quote! {
                 fn labels(&self) -> std::option::Option<std::boxed::Box<dyn std::iter::Iterator<Item = miette::LabeledSpan> + '_>>
             },
+            Self::ContextSnippets => quote! {
+                fn context_snippets(&self) -> std::option::Option<std::boxed::Box<dyn std::iter::Iterator<Item = miette::LabeledSpan> + '_>>
+            },
             Self::SourceCode => quote! {
                 fn source_code(&self) -> std::option::Option<&dyn miette::SourceCode>
             },
+            Self::SourceCodeOwned => quote! {
+                fn source_code_owned(&self) -> std::option::Option<std::boxed::Box<dyn miette::SourceCode + '_>>
+            },
             Self::DiagnosticSource => quote! {
                 fn diagnostic_source(&self) -> std::option::Option<&dyn miette::Diagnostic>
             },
@@ -85,7 +110,20 @@ impl WhichFn {
     }
 
     pub fn catchall_arm(&self) -> TokenStream {
-        quote! { _ => std::option::Option::None }
+        match self {
+            Self::IsFatal => quote! {
+                _ => std::matches!(
+                    miette::Diagnostic::severity(self),
+                    std::option::Option::None
+                        | std::option::Option::Some(miette::Severity::Error)
+                )
+            },
+            Self::SourceCodeOwned => quote! {
+                _ => miette::Diagnostic::source_code(self)
+                    .map(|src| std::boxed::Box::new(src) as std::boxed::Box<dyn miette::SourceCode + '_>)
+            },
+            _ => quote! { _ => std::option::Option::None },
+        }
     }
 }
 
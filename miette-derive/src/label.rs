@@ -133,17 +133,6 @@ impl Labels {
                     let LabelAttr { label, lbl_ty } =
                         syn::parse2::<LabelAttr>(attr.meta.to_token_stream())?;
 
-                    if lbl_ty == LabelType::Primary
-                        && labels
-                            .iter()
-                            .any(|l: &Label| l.lbl_ty == LabelType::Primary)
-                    {
-                        return Err(syn::Error::new(
-                            field.span(),
-                            "Cannot have more than one primary label.",
-                        ));
-                    }
-
                     labels.push(Label {
                         label,
                         span,
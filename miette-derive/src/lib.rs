@@ -7,13 +7,16 @@ mod code;
 mod diagnostic;
 mod diagnostic_arg;
 mod diagnostic_source;
+mod fatal;
 mod fmt;
 mod forward;
 mod help;
 mod label;
+mod long_message;
 mod related;
 mod severity;
 mod source_code;
+mod title;
 mod url;
 mod utils;
 
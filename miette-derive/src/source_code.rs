@@ -5,7 +5,7 @@ use syn::spanned::Spanned;
 use crate::{
     diagnostic::{DiagnosticConcreteArgs, DiagnosticDef},
     forward::WhichFn,
-    utils::{display_pat_members, gen_all_variants_with},
+    utils::{display_pat_members, gen_all_variants_with, gen_unused_pat},
 };
 
 pub struct SourceCode {
@@ -114,4 +114,21 @@ impl SourceCode {
             },
         )
     }
+
+    /// Forwards `source_code_owned` for variants that forward everything
+    /// else, unless they have a `#[source_code]` of their own.
+    pub(crate) fn gen_enum_owned(variants: &[DiagnosticDef]) -> Option<TokenStream> {
+        gen_all_variants_with(
+            variants,
+            WhichFn::SourceCodeOwned,
+            |ident, fields, DiagnosticConcreteArgs { source_code, .. }| {
+                source_code.as_ref()?;
+                let fields = gen_unused_pat(fields);
+                Some(quote! {
+                    Self::#ident #fields => miette::Diagnostic::source_code(self)
+                        .map(|src| std::boxed::Box::new(src) as std::boxed::Box<dyn miette::SourceCode + '_>),
+                })
+            },
+        )
+    }
 }
@@ -18,12 +18,23 @@ use crate::{
 pub enum Url {
     Display(Display),
     DocsRs,
+    Template(syn::LitStr),
 }
 
 impl Parse for Url {
     fn parse(input: ParseStream) -> syn::Result<Self> {
         let ident = input.parse::<syn::Ident>()?;
-        if ident == "url" {
+        if ident == "url_template" {
+            let template = if input.peek(syn::token::Paren) {
+                let content;
+                parenthesized!(content in input);
+                content.parse()?
+            } else {
+                input.parse::<Token![=]>()?;
+                input.parse()?
+            };
+            Ok(Url::Template(template))
+        } else if ident == "url" {
             let la = input.lookahead1();
             if la.peek(syn::token::Paren) {
                 let content;
@@ -73,6 +84,11 @@ impl Url {
             WhichFn::Url,
             |ident, fields, DiagnosticConcreteArgs { url, .. }| {
                 let (pat, fmt, args) = match url.as_ref()? {
+                    Url::Template(template) => {
+                        let pat = gen_unused_pat(fields);
+                        let body = gen_template(template);
+                        return Some(quote! { Self::#ident #pat => #body, });
+                    }
                     // fall through to `_ => None` below
                     Url::Display(display) => {
                         let (display_pat, display_members) = display_pat_members(fields);
@@ -108,6 +124,14 @@ impl Url {
         fields: &Fields,
     ) -> Option<TokenStream> {
         let (pat, fmt, args) = match self {
+            Url::Template(template) => {
+                let body = gen_template(template);
+                return Some(quote! {
+                    fn url(&self) -> std::option::Option<std::boxed::Box<dyn std::fmt::Display + '_>> {
+                        #body
+                    }
+                });
+            }
             Url::Display(display) => {
                 let (display_pat, display_members) = display_pat_members(fields);
                 let (fmt, args) = display.expand_shorthand_cloned(&display_members);
@@ -137,3 +161,14 @@ impl Url {
         })
     }
 }
+
+/// Substitutes the diagnostic's code into `template` when the URL is
+/// requested, so the code can come from anywhere (including a forward).
+fn gen_template(template: &syn::LitStr) -> TokenStream {
+    quote! {
+        miette::Diagnostic::code(self).map(|code| {
+            std::boxed::Box::new(#template.replace("{code}", &code.to_string()))
+                as std::boxed::Box<dyn std::fmt::Display>
+        })
+    }
+}
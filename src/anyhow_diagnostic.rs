@@ -0,0 +1,64 @@
+use std::{
+    error::Error,
+    fmt::{self, Debug, Display},
+};
+
+use crate::Diagnostic;
+
+/// Wrapper that bridges an [`anyhow::Error`] into a [`Diagnostic`], so code
+/// that's migrating from `anyhow` can hand its errors to `miette`.
+///
+/// The wrapper displays the outermost message of the `anyhow::Error`, and
+/// exposes the rest of its [`chain`](anyhow::Error::chain) (context layers
+/// included) through [`Error::source`], so they render as the diagnostic's
+/// causes.
+///
+/// ```rust
+/// use anyhow::Context;
+/// use miette::{AnyhowDiagnostic, Report};
+///
+/// let error = Err::<(), _>(anyhow::anyhow!("file not found"))
+///     .context("failed to read config")
+///     .unwrap_err();
+/// let report = Report::new(AnyhowDiagnostic::from(error));
+/// assert_eq!(report.to_string(), "failed to read config");
+/// ```
+pub struct AnyhowDiagnostic(anyhow::Error);
+
+impl AnyhowDiagnostic {
+    /// Wrap an [`anyhow::Error`].
+    pub fn new(error: anyhow::Error) -> Self {
+        Self(error)
+    }
+
+    /// Returns the wrapped [`anyhow::Error`].
+    pub fn into_inner(self) -> anyhow::Error {
+        self.0
+    }
+}
+
+impl From<anyhow::Error> for AnyhowDiagnostic {
+    fn from(error: anyhow::Error) -> Self {
+        Self(error)
+    }
+}
+
+impl Debug for AnyhowDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Debug::fmt(&self.0, f)
+    }
+}
+
+impl Display for AnyhowDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Display::fmt(&self.0, f)
+    }
+}
+
+impl Error for AnyhowDiagnostic {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.0.source()
+    }
+}
+
+impl Diagnostic for AnyhowDiagnostic {}
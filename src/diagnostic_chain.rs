@@ -3,6 +3,8 @@ Iterate over error `.diagnostic_source()` chains.
 */
 
 use crate::protocol::Diagnostic;
+#[cfg(feature = "fancy-base")]
+use crate::MietteError;
 
 /// Iterator of a chain of cause errors.
 #[derive(Clone, Default)]
@@ -64,7 +66,7 @@ pub(crate) enum ErrorKind<'a> {
 
 impl<'a> ErrorKind<'a> {
     fn get_nested(&self) -> Option<ErrorKind<'a>> {
-        match self {
+        match *self {
             ErrorKind::Diagnostic(d) => d
                 .diagnostic_source()
                 .map(ErrorKind::Diagnostic)
@@ -72,6 +74,19 @@ impl<'a> ErrorKind<'a> {
             ErrorKind::StdError(e) => e.source().map(ErrorKind::StdError),
         }
     }
+
+    /// Get this error as a [`Diagnostic`], if it is one. Plain errors can't be
+    /// downcast to an arbitrary `dyn Diagnostic`, so only miette's own error
+    /// types are recognized among them.
+    #[cfg(feature = "fancy-base")]
+    pub(crate) fn as_diagnostic(&self) -> Option<&'a dyn Diagnostic> {
+        match self {
+            ErrorKind::Diagnostic(d) => Some(*d),
+            ErrorKind::StdError(e) => e
+                .downcast_ref::<MietteError>()
+                .map(|e| e as &dyn Diagnostic),
+        }
+    }
 }
 
 impl<'a> std::fmt::Debug for ErrorKind<'a> {
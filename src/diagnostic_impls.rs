@@ -19,6 +19,14 @@ impl Diagnostic for Infallible {
         match *self {}
     }
 
+    fn title<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        match *self {}
+    }
+
+    fn long_message<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        match *self {}
+    }
+
     fn url<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
         match *self {}
     }
@@ -40,6 +48,62 @@ impl Diagnostic for Infallible {
     }
 }
 
+/// Lets a borrowed diagnostic, including a `&dyn Diagnostic`, be passed
+/// wherever an `impl Diagnostic` is expected.
+impl<T: Diagnostic + ?Sized> Diagnostic for &T {
+    fn code<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        (**self).code()
+    }
+
+    fn severity(&self) -> Option<Severity> {
+        (**self).severity()
+    }
+
+    fn is_fatal(&self) -> bool {
+        (**self).is_fatal()
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        (**self).help()
+    }
+
+    fn title<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        (**self).title()
+    }
+
+    fn long_message<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        (**self).long_message()
+    }
+
+    fn url<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        (**self).url()
+    }
+
+    fn source_code(&self) -> Option<&dyn SourceCode> {
+        (**self).source_code()
+    }
+
+    fn source_code_owned(&self) -> Option<Box<dyn SourceCode + '_>> {
+        (**self).source_code_owned()
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+        (**self).labels()
+    }
+
+    fn context_snippets(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+        (**self).context_snippets()
+    }
+
+    fn related<'a>(&'a self) -> Option<Box<dyn Iterator<Item = &'a dyn Diagnostic> + 'a>> {
+        (**self).related()
+    }
+
+    fn diagnostic_source(&self) -> Option<&dyn Diagnostic> {
+        (**self).diagnostic_source()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -52,4 +116,21 @@ mod tests {
     fn infallible() {
         let _ = Report::new::<Infallible>;
     }
+
+    #[test]
+    fn reference() {
+        fn render<D: Diagnostic>(diagnostic: D) -> String {
+            let mut out = String::new();
+            crate::NarratableReportHandler::new()
+                .render_report(&mut out, &diagnostic)
+                .unwrap();
+            out
+        }
+
+        let diagnostic = crate::MietteDiagnostic::new("oops").with_code("oops::code");
+        let expected = "oops\n    Diagnostic severity: error\ndiagnostic code: oops::code\n";
+        assert_eq!(render(&diagnostic), expected);
+        let dyn_diagnostic: &dyn Diagnostic = &diagnostic;
+        assert_eq!(render(dyn_diagnostic), expected);
+    }
 }
@@ -0,0 +1,77 @@
+use crate::{MietteError, SourceCode, SourceSpan, SpanContents};
+
+/**
+[`SourceCode`] holding two versions of a text, "before" and "after" some
+change, for diagnostics that want to show both (e.g. a migration or lint
+tool's suggested rewrite).
+
+Offsets into a `DiffSource` address the "before" text followed directly by
+the "after" text, so labels pointing into the "after" text should be
+offset by [`DiffSource::split`]; [`DiffSource::after_span`] does this for
+you. When rendered with
+[`GraphicalReportHandler::with_diff_view`](crate::GraphicalReportHandler::with_diff_view),
+the two versions are shown side by side, each with its own labels.
+Otherwise, it's rendered like any other source.
+
+```rust
+use miette::{DiffSource, SourceSpan};
+
+let src = DiffSource::new("let x = 1;\n", "let x = 2;\n");
+assert_eq!(src.split(), 11);
+assert_eq!(src.after_span((8, 1)), SourceSpan::from((19, 1)));
+```
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiffSource {
+    text: String,
+    split: usize,
+}
+
+impl DiffSource {
+    /// Create a new `DiffSource` from the texts before and after a change.
+    pub fn new(before: impl AsRef<str>, after: impl AsRef<str>) -> Self {
+        let before = before.as_ref();
+        Self {
+            text: format!("{}{}", before, after.as_ref()),
+            split: before.len(),
+        }
+    }
+
+    /// The text before the change.
+    pub fn before(&self) -> &str {
+        &self.text[..self.split]
+    }
+
+    /// The text after the change.
+    pub fn after(&self) -> &str {
+        &self.text[self.split..]
+    }
+
+    /// Offset at which the "after" text starts.
+    pub fn split(&self) -> usize {
+        self.split
+    }
+
+    /// Converts a span into the "after" text into a span into this
+    /// `DiffSource`.
+    pub fn after_span(&self, span: impl Into<SourceSpan>) -> SourceSpan {
+        let span = span.into();
+        (self.split + span.offset(), span.len()).into()
+    }
+}
+
+impl SourceCode for DiffSource {
+    fn read_span<'a>(
+        &'a self,
+        span: &SourceSpan,
+        context_lines_before: usize,
+        context_lines_after: usize,
+    ) -> Result<Box<dyn SpanContents<'a> + 'a>, MietteError> {
+        self.text
+            .read_span(span, context_lines_before, context_lines_after)
+    }
+
+    fn as_diff(&self) -> Option<&DiffSource> {
+        Some(self)
+    }
+}
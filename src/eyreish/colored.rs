@@ -0,0 +1,42 @@
+use core::fmt::{self, Debug, Display};
+
+use super::Report;
+use crate::{MietteHandlerOpts, ReportHandler};
+
+/// A [`Report`] that always renders graphically and with color, as returned
+/// by [`Report::colored`].
+///
+/// Both [`Debug`] and [`Display`] render the report with a fresh
+/// [`MietteHandler`](crate::MietteHandler) that has color forced on, whatever
+/// the environment (`NO_COLOR`, a non-terminal output, ...) would otherwise
+/// say. This is meant for logging setups that always want ANSI output. Other
+/// settings are detected as usual.
+pub struct ColoredReport<'a> {
+    report: &'a Report,
+}
+
+impl<'a> ColoredReport<'a> {
+    pub(crate) fn new(report: &'a Report) -> Self {
+        Self { report }
+    }
+
+    fn render(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        MietteHandlerOpts::new()
+            .color(true)
+            .force_graphical(true)
+            .build()
+            .debug(self.report.as_ref(), f)
+    }
+}
+
+impl Debug for ColoredReport<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.render(f)
+    }
+}
+
+impl Display for ColoredReport<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.render(f)
+    }
+}
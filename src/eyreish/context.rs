@@ -171,6 +171,10 @@ where
         self.error.severity()
     }
 
+    fn is_fatal(&self) -> bool {
+        self.error.is_fatal()
+    }
+
     fn help<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
         self.error.help()
     }
@@ -183,10 +187,18 @@ where
         self.error.labels()
     }
 
+    fn context_snippets(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+        self.error.context_snippets()
+    }
+
     fn source_code(&self) -> Option<&dyn crate::SourceCode> {
         self.error.source_code()
     }
 
+    fn source_code_owned(&self) -> Option<Box<dyn crate::SourceCode + '_>> {
+        self.error.source_code_owned()
+    }
+
     fn related<'a>(&'a self) -> Option<Box<dyn Iterator<Item = &'a dyn Diagnostic> + 'a>> {
         self.error.related()
     }
@@ -204,6 +216,10 @@ where
         unsafe { ErrorImpl::diagnostic(self.error.inner.by_ref()).severity() }
     }
 
+    fn is_fatal(&self) -> bool {
+        unsafe { ErrorImpl::diagnostic(self.error.inner.by_ref()).is_fatal() }
+    }
+
     fn help<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
         unsafe { ErrorImpl::diagnostic(self.error.inner.by_ref()).help() }
     }
@@ -216,10 +232,18 @@ where
         unsafe { ErrorImpl::diagnostic(self.error.inner.by_ref()).labels() }
     }
 
+    fn context_snippets(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+        unsafe { ErrorImpl::diagnostic(self.error.inner.by_ref()).context_snippets() }
+    }
+
     fn source_code(&self) -> Option<&dyn crate::SourceCode> {
         self.error.source_code()
     }
 
+    fn source_code_owned(&self) -> Option<Box<dyn crate::SourceCode + '_>> {
+        self.error.source_code_owned()
+    }
+
     fn related<'a>(&'a self) -> Option<Box<dyn Iterator<Item = &'a dyn Diagnostic> + 'a>> {
         self.error.related()
     }
@@ -1,15 +1,19 @@
-use core::any::TypeId;
+use core::any::{Any, TypeId};
 use core::fmt::{self, Debug, Display};
 use core::mem::ManuallyDrop;
 use core::ptr::{self, NonNull};
+use std::backtrace::{Backtrace, BacktraceStatus};
+use std::collections::HashMap;
 use std::error::Error as StdError;
 
 use super::ptr::{Mut, Own, Ref};
-use super::Report;
+#[cfg(feature = "fancy-base")]
+use super::ColoredReport;
 use super::ReportHandler;
+use super::{MemoizedReport, Report};
 use crate::chain::Chain;
-use crate::eyreish::wrapper::WithSourceCode;
-use crate::{Diagnostic, SourceCode};
+use crate::eyreish::wrapper::{MapLabels, WithSourceCode};
+use crate::{Diagnostic, LabeledSpan, SourceCode};
 use core::ops::{Deref, DerefMut};
 
 impl Report {
@@ -82,6 +86,21 @@ impl Report {
     ///
     /// Boxed `Diagnostic`s don't implement `Diagnostic` themselves due to trait coherence issues.
     /// This method allows you to create a `Report` from a boxed `Diagnostic`.
+    ///
+    /// A `Box<dyn Diagnostic>` without the `Send + Sync` bounds can't be
+    /// turned into a `Report`, since a `Report` must always be safe to send
+    /// across threads. If you don't control where such a box comes from, you
+    /// can take a thread-safe snapshot of it with
+    /// [`OwnedDiagnostic`](crate::OwnedDiagnostic) instead (note that the
+    /// snapshot doesn't include the diagnostic's cause chain):
+    ///
+    /// ```rust
+    /// use miette::{Diagnostic, MietteDiagnostic, OwnedDiagnostic, Report};
+    ///
+    /// let local: Box<dyn Diagnostic> = Box::new(MietteDiagnostic::new("oops"));
+    /// let report = Report::new(OwnedDiagnostic::from(&*local));
+    /// assert_eq!(report.to_string(), "oops");
+    /// ```
     #[cfg_attr(track_caller, track_caller)]
     pub fn new_boxed(error: Box<dyn Diagnostic + Send + Sync + 'static>) -> Self {
         Report::from_boxed(error)
@@ -90,6 +109,21 @@ impl Report {
     #[cfg_attr(track_caller, track_caller)]
     #[cold]
     pub(crate) fn from_std<E>(error: E) -> Self
+    where
+        E: Diagnostic + Send + Sync + 'static,
+    {
+        Report::from_std_with(error, None, capture_backtrace())
+    }
+
+    /// Like [`Report::from_std`], but with the given handler (or a newly
+    /// captured one, if `None`) and backtrace, for reports rebuilt around an
+    /// existing one.
+    #[cfg_attr(track_caller, track_caller)]
+    fn from_std_with<E>(
+        error: E,
+        handler: Option<Box<dyn ReportHandler>>,
+        backtrace: Option<Backtrace>,
+    ) -> Self
     where
         E: Diagnostic + Send + Sync + 'static,
     {
@@ -104,9 +138,9 @@ impl Report {
         };
 
         // Safety: passing vtable that operates on the right type E.
-        let handler = Some(super::capture_handler(&error));
+        let handler = handler.or_else(|| Some(super::capture_handler(&error)));
 
-        unsafe { Report::construct(error, vtable, handler) }
+        unsafe { Report::construct(error, vtable, handler, backtrace) }
     }
 
     #[cfg_attr(track_caller, track_caller)]
@@ -131,7 +165,7 @@ impl Report {
         // vtable to allow casting the MessageError<M> to M.
         let handler = Some(super::capture_handler(&error));
 
-        unsafe { Report::construct(error, vtable, handler) }
+        unsafe { Report::construct(error, vtable, handler, capture_backtrace()) }
     }
 
     #[cfg_attr(track_caller, track_caller)]
@@ -156,15 +190,26 @@ impl Report {
         // Safety: passing vtable that operates on the right type.
         let handler = Some(super::capture_handler(&error));
 
-        unsafe { Report::construct(error, vtable, handler) }
+        unsafe { Report::construct(error, vtable, handler, capture_backtrace()) }
     }
 
     #[cfg_attr(track_caller, track_caller)]
     #[cold]
     pub(crate) fn from_boxed(error: Box<dyn Diagnostic + Send + Sync>) -> Self {
+        Report::from_boxed_with(error, None, capture_backtrace())
+    }
+
+    /// Like [`Report::from_boxed`], but with the given handler (or a newly
+    /// captured one, if `None`) and backtrace.
+    #[cfg_attr(track_caller, track_caller)]
+    fn from_boxed_with(
+        error: Box<dyn Diagnostic + Send + Sync>,
+        handler: Option<Box<dyn ReportHandler>>,
+        backtrace: Option<Backtrace>,
+    ) -> Self {
         use super::wrapper::BoxedError;
         let error = BoxedError(error);
-        let handler = Some(super::capture_handler(&error));
+        let handler = handler.or_else(|| Some(super::capture_handler(&error)));
 
         let vtable = &ErrorVTable {
             object_drop: object_drop::<BoxedError>,
@@ -178,12 +223,9 @@ impl Report {
 
         // Safety: BoxedError is repr(transparent) so it is okay for the vtable
         // to allow casting to Box<dyn StdError + Send + Sync>.
-        unsafe { Report::construct(error, vtable, handler) }
+        unsafe { Report::construct(error, vtable, handler, backtrace) }
     }
 
-    // Takes backtrace as argument rather than capturing it here so that the
-    // user sees one fewer layer of wrapping noise in the backtrace.
-    //
     // Unsafe because the given vtable must have sensible behavior on the error
     // value of type E.
     #[cold]
@@ -191,6 +233,7 @@ impl Report {
         error: E,
         vtable: &'static ErrorVTable,
         handler: Option<Box<dyn ReportHandler>>,
+        backtrace: Option<Backtrace>,
     ) -> Self
     where
         E: Diagnostic + Send + Sync + 'static,
@@ -198,6 +241,8 @@ impl Report {
         let inner = Box::new(ErrorImpl {
             vtable,
             handler,
+            extensions: Extensions::new(),
+            backtrace,
             _object: error,
         });
         // Erase the concrete type of E from the compile-time type system. This
@@ -224,6 +269,9 @@ impl Report {
         D: Display + Send + Sync + 'static,
     {
         let handler = unsafe { self.inner.by_mut().deref_mut().handler.take() };
+        let extensions =
+            unsafe { core::mem::take(&mut self.inner.by_mut().deref_mut().extensions) };
+        let backtrace = unsafe { self.inner.by_mut().deref_mut().backtrace.take() };
         let error: ContextError<D, Report> = ContextError { msg, error: self };
 
         let vtable = &ErrorVTable {
@@ -237,7 +285,9 @@ impl Report {
         };
 
         // Safety: passing vtable that operates on the right type.
-        unsafe { Report::construct(error, vtable, handler) }
+        let report = unsafe { Report::construct(error, vtable, handler, backtrace) };
+        unsafe { report.inner.by_mut().deref_mut().extensions = extensions };
+        report
     }
 
     /// Compatibility re-export of `wrap_err` for interop with `anyhow`
@@ -248,6 +298,21 @@ impl Report {
         self.wrap_err(msg)
     }
 
+    /// Wrap this error with a context message and attach source code to the
+    /// result, in one go. Same as
+    /// `report.wrap_err(msg).with_source_code(source_code)`.
+    ///
+    /// The error is wrapped first and the source attached to the wrapper, so
+    /// the wrapped error's labels (which the context message picks up) are
+    /// resolved against `source_code`, unless that error already had source
+    /// code of its own.
+    pub fn context_with_source<D>(self, msg: D, source_code: impl SourceCode + 'static) -> Self
+    where
+        D: Display + Send + Sync + 'static,
+    {
+        self.wrap_err(msg).with_source_code(source_code)
+    }
+
     /// An iterator of the chain of source errors contained by this Report.
     ///
     /// This iterator will visit every error in the cause chain of this error
@@ -391,6 +456,46 @@ impl Report {
         }
     }
 
+    /// Wrap this report so that it's only rendered once, no matter how many
+    /// times it's formatted afterwards. See [`MemoizedReport`].
+    ///
+    /// ```rust
+    /// use miette::miette;
+    ///
+    /// let report = miette!("request failed").memoized();
+    /// // Rendered once here...
+    /// let logged = format!("{:?}", report);
+    /// // ...and reused here.
+    /// assert_eq!(logged, format!("{:?}", report));
+    /// ```
+    pub fn memoized(self) -> MemoizedReport {
+        MemoizedReport::new(self)
+    }
+
+    /// Returns an adapter that renders this report graphically with color
+    /// forced on, regardless of the environment or the handler the report
+    /// captured. See [`ColoredReport`].
+    #[cfg(feature = "fancy-base")]
+    pub fn colored(&self) -> ColoredReport<'_> {
+        ColoredReport::new(self)
+    }
+
+    /// Renders this report with its handler (the same as formatting it with
+    /// `{:?}`) and writes it, followed by a newline, to `writer`.
+    pub fn write_to(&self, mut writer: impl std::io::Write) -> std::io::Result<()> {
+        writeln!(writer, "{:?}", self)
+    }
+
+    /// Renders this report with its handler and prints it to stdout.
+    pub fn print(&self) -> std::io::Result<()> {
+        self.write_to(std::io::stdout().lock())
+    }
+
+    /// Renders this report with its handler and prints it to stderr.
+    pub fn eprint(&self) -> std::io::Result<()> {
+        self.write_to(std::io::stderr().lock())
+    }
+
     /// Get a reference to the Handler for this Report.
     pub fn handler(&self) -> &dyn ReportHandler {
         unsafe {
@@ -417,13 +522,185 @@ impl Report {
         }
     }
 
+    /// Parse a diagnostic serialized as JSON, either by
+    /// [`JSONReportHandler`](crate::JSONReportHandler) or from an
+    /// [`OwnedDiagnostic`](crate::OwnedDiagnostic), into a Report that can be
+    /// rendered by any handler.
+    ///
+    /// Cause chains aren't restored, and neither is source code unless it
+    /// was serialized with an `OwnedDiagnostic`. Requires the `json`
+    /// feature.
+    ///
+    /// ```
+    /// let report = miette::Report::from_json(r#"{"message": "oops!", "severity": "warning"}"#)?;
+    /// assert_eq!(report.to_string(), "oops!");
+    /// assert_eq!(report.severity(), Some(miette::Severity::Warning));
+    /// # Ok::<(), serde_json::Error>(())
+    /// ```
+    #[cfg(feature = "json")]
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str::<crate::OwnedDiagnostic>(json).map(Report::new)
+    }
+
+    /// Get the backtrace captured when this Report was created, if any.
+    ///
+    /// Like [`std::backtrace::Backtrace::capture`], backtraces are only
+    /// captured if the `RUST_LIB_BACKTRACE` or `RUST_BACKTRACE` environment
+    /// variables enable them. [`Report::wrap_err`] keeps the backtrace of the
+    /// wrapped Report.
+    ///
+    /// With the `nightly-backtrace` feature, a backtrace provided by the
+    /// error itself (through [`std::error::Error::provide`]) takes
+    /// precedence. This includes errors converted with
+    /// [`IntoDiagnostic`](crate::IntoDiagnostic).
+    pub fn backtrace(&self) -> Option<&Backtrace> {
+        #[cfg(feature = "nightly-backtrace")]
+        {
+            let error = unsafe { ErrorImpl::error(self.inner.by_ref()) };
+            if let Some(backtrace) = std::error::request_ref::<Backtrace>(error) {
+                return Some(backtrace);
+            }
+        }
+        unsafe { self.inner.by_ref().deref().backtrace.as_ref() }
+    }
+
+    /// Replace the Handler for this Report, e.g. to re-render a report that
+    /// was created elsewhere with a different handler.
+    pub fn set_handler(&mut self, handler: Box<dyn ReportHandler>) {
+        unsafe {
+            self.inner.by_mut().deref_mut().handler = Some(handler);
+        }
+    }
+
+    /// Attach an arbitrary typed value to this Report, replacing any value of
+    /// the same type that was already attached.
+    ///
+    /// Attached values are carried along when the Report is wrapped with
+    /// [`Report::wrap_err`] or [`WrapErr`](crate::WrapErr), and can be
+    /// retrieved later with [`Report::get`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use miette::{miette, Report};
+    ///
+    /// #[derive(Debug, PartialEq)]
+    /// struct RequestId(u64);
+    ///
+    /// let mut report = miette!("request failed");
+    /// report.insert(RequestId(42));
+    /// let report = report.wrap_err("while handling request");
+    /// assert_eq!(report.get::<RequestId>(), Some(&RequestId(42)));
+    /// ```
+    pub fn insert<T>(&mut self, value: T) -> Option<T>
+    where
+        T: Send + Sync + 'static,
+    {
+        let extensions = unsafe { &mut self.inner.by_mut().deref_mut().extensions };
+        extensions
+            .insert(TypeId::of::<T>(), Box::new(value))
+            .and_then(|old| old.downcast().ok())
+            .map(|old| *old)
+    }
+
+    /// Get a reference to a value of type `T` previously attached with
+    /// [`Report::insert`], if any.
+    pub fn get<T>(&self) -> Option<&T>
+    where
+        T: Send + Sync + 'static,
+    {
+        let extensions = unsafe { &self.inner.by_ref().deref().extensions };
+        extensions
+            .get(&TypeId::of::<T>())
+            .and_then(|value| value.downcast_ref())
+    }
+
+    /// Get a mutable reference to a value of type `T` previously attached
+    /// with [`Report::insert`], if any.
+    pub fn get_mut<T>(&mut self) -> Option<&mut T>
+    where
+        T: Send + Sync + 'static,
+    {
+        let extensions = unsafe { &mut self.inner.by_mut().deref_mut().extensions };
+        extensions
+            .get_mut(&TypeId::of::<T>())
+            .and_then(|value| value.downcast_mut())
+    }
+
     /// Provide source code for this error
     pub fn with_source_code(self, source_code: impl SourceCode + 'static) -> Report {
-        WithSourceCode {
-            source_code,
-            error: self,
-        }
-        .into()
+        self.rewrap(|error| WithSourceCode { source_code, error })
+    }
+
+    /// Transform each of this error's labels with `map`, e.g. to shift them
+    /// when the source they point into turns out to be a slice of a larger
+    /// one.
+    ///
+    /// ```rust
+    /// use miette::{miette, LabeledSpan};
+    ///
+    /// let report = miette!(labels = vec![LabeledSpan::at(0..3, "here")], "oops")
+    ///     .map_labels(|label| {
+    ///         LabeledSpan::new_with_span(
+    ///             label.label().map(String::from),
+    ///             (label.offset() + 10, label.len()),
+    ///         )
+    ///     });
+    /// let label = report.labels().unwrap().next().unwrap();
+    /// assert_eq!(label.offset(), 10);
+    /// ```
+    pub fn map_labels<F>(self, map: F) -> Report
+    where
+        F: Fn(LabeledSpan) -> LabeledSpan + Send + Sync + 'static,
+    {
+        self.rewrap(|error| MapLabels { error, map })
+    }
+
+    /// Rebuilds this report around `wrap(self)`, carrying over its handler,
+    /// extensions and backtrace like [`Report::wrap_err`] does.
+    fn rewrap<E>(self, wrap: impl FnOnce(Report) -> E) -> Report
+    where
+        E: Diagnostic + Send + Sync + 'static,
+    {
+        let handler = unsafe { self.inner.by_mut().deref_mut().handler.take() };
+        let extensions =
+            unsafe { core::mem::take(&mut self.inner.by_mut().deref_mut().extensions) };
+        let backtrace = unsafe { self.inner.by_mut().deref_mut().backtrace.take() };
+        let report = Report::from_std_with(wrap(self), handler, backtrace);
+        unsafe { report.inner.by_mut().deref_mut().extensions = extensions };
+        report
+    }
+
+    /// Applies `map` to the boxed inner diagnostic and rebuilds the report
+    /// around its result, e.g. to redact source code or rewrite messages in
+    /// a report received from elsewhere. The report's handler, extensions
+    /// and backtrace are carried over.
+    ///
+    /// ```rust
+    /// use miette::{miette, Diagnostic, MietteDiagnostic};
+    ///
+    /// let report = miette!(code = "secret::code", "password is hunter2")
+    ///     .map_diagnostic(|diag| {
+    ///         let mut redacted = MietteDiagnostic::new("[redacted]");
+    ///         if let Some(code) = diag.code() {
+    ///             redacted = redacted.with_code(code.to_string());
+    ///         }
+    ///         Box::new(redacted)
+    ///     });
+    /// assert_eq!(report.to_string(), "[redacted]");
+    /// assert_eq!(report.code().unwrap().to_string(), "secret::code");
+    /// ```
+    pub fn map_diagnostic<F>(self, map: F) -> Report
+    where
+        F: FnOnce(Box<dyn Diagnostic + Send + Sync>) -> Box<dyn Diagnostic + Send + Sync>,
+    {
+        let handler = unsafe { self.inner.by_mut().deref_mut().handler.take() };
+        let extensions =
+            unsafe { core::mem::take(&mut self.inner.by_mut().deref_mut().extensions) };
+        let backtrace = unsafe { self.inner.by_mut().deref_mut().backtrace.take() };
+        let report = Report::from_boxed_with(map(self.into()), handler, backtrace);
+        unsafe { report.inner.by_mut().deref_mut().extensions = extensions };
+        report
     }
 
     /// Construct a [`Report`] directly from an error-like type
@@ -446,6 +723,33 @@ where
     }
 }
 
+macro_rules! impl_from_tuple {
+    ($first:ident $(, $rest:ident)+) => {
+        /// Treats the first diagnostic as the report's own, and the rest as
+        /// its [related](Diagnostic::related) diagnostics, for quick ad-hoc
+        /// aggregation.
+        impl<$first $(, $rest)+> From<($first, $($rest),+)> for Report
+        where
+            $first: Diagnostic + Send + Sync + 'static,
+            $($rest: Diagnostic + Send + Sync + 'static,)+
+        {
+            #[cfg_attr(track_caller, track_caller)]
+            #[cold]
+            #[allow(non_snake_case)]
+            fn from(($first, $($rest),+): ($first, $($rest),+)) -> Self {
+                Report::from_std(super::wrapper::AggregateError {
+                    primary: Box::new($first),
+                    related: vec![$(Box::new($rest)),+],
+                })
+            }
+        }
+    };
+}
+
+impl_from_tuple!(A, B);
+impl_from_tuple!(A, B, C);
+impl_from_tuple!(A, B, C, D);
+
 impl Deref for Report {
     type Target = dyn Diagnostic + Send + Sync + 'static;
 
@@ -669,11 +973,19 @@ where
     }
 }
 
+/// Captures a backtrace for a newly created [`Report`], if backtraces are
+/// enabled.
+fn capture_backtrace() -> Option<Backtrace> {
+    Some(Backtrace::capture()).filter(|backtrace| backtrace.status() == BacktraceStatus::Captured)
+}
+
 // repr C to ensure that E remains in the final position.
 #[repr(C)]
 pub(crate) struct ErrorImpl<E> {
     vtable: &'static ErrorVTable,
     pub(crate) handler: Option<Box<dyn ReportHandler>>,
+    extensions: Extensions,
+    backtrace: Option<Backtrace>,
     // NOTE: Don't use directly. Use only through vtable. Erased type may have
     // different alignment.
     _object: E,
@@ -689,6 +1001,9 @@ pub(crate) struct ContextError<D, E> {
 
 type ErasedErrorImpl = ErrorImpl<()>;
 
+// Typed values attached with `Report::insert`, keyed by their type.
+type Extensions = HashMap<TypeId, Box<dyn Any + Send + Sync>>;
+
 // Safety: `ErrorVTable` must be the first field of `ErrorImpl`
 unsafe fn vtable(p: NonNull<ErasedErrorImpl>) -> &'static ErrorVTable {
     (p.as_ptr() as *const &'static ErrorVTable).read()
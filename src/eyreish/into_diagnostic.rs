@@ -1,17 +1,35 @@
-use thiserror::Error;
+use std::{error::Error, fmt};
 
 use crate::{Diagnostic, Report};
 
 /// Convenience [`Diagnostic`] that can be used as an "anonymous" wrapper for
 /// Errors. This is intended to be paired with [`IntoDiagnostic`].
-#[derive(Debug, Error)]
-#[error(transparent)]
-pub(crate) struct DiagnosticError(pub(crate) Box<dyn std::error::Error + Send + Sync + 'static>);
+#[derive(Debug)]
+pub(crate) struct DiagnosticError(pub(crate) Box<dyn Error + Send + Sync + 'static>);
+
+impl fmt::Display for DiagnosticError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+// Written out rather than derived, so that `provide` can be forwarded too.
+impl Error for DiagnosticError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.0.source()
+    }
+
+    #[cfg(feature = "nightly-backtrace")]
+    fn provide<'a>(&'a self, request: &mut std::error::Request<'a>) {
+        self.0.provide(request)
+    }
+}
+
 impl Diagnostic for DiagnosticError {}
 
 /**
 Convenience trait that adds a [`.into_diagnostic()`](IntoDiagnostic::into_diagnostic) method that converts a type implementing
-[`std::error::Error`] to a [`Result<T, Report>`].
+[`std::error::Error`] (or a `Box<dyn std::error::Error + Send + Sync>`) to a [`Result<T, Report>`].
 
 ## Warning
 
@@ -26,8 +44,13 @@ pub trait IntoDiagnostic<T, E> {
     fn into_diagnostic(self) -> Result<T, Report>;
 }
 
-impl<T, E: std::error::Error + Send + Sync + 'static> IntoDiagnostic<T, E> for Result<T, E> {
+// Bounding on `Into` rather than `std::error::Error` also covers
+// `Box<dyn Error + Send + Sync>`, which doesn't implement `Error` itself.
+impl<T, E> IntoDiagnostic<T, E> for Result<T, E>
+where
+    E: Into<Box<dyn std::error::Error + Send + Sync + 'static>>,
+{
     fn into_diagnostic(self) -> Result<T, Report> {
-        self.map_err(|e| DiagnosticError(Box::new(e)).into())
+        self.map_err(|e| DiagnosticError(e.into()).into())
     }
 }
@@ -298,3 +298,29 @@ macro_rules! diagnostic {
         diag
     }};
 }
+
+/// Construct an ad-hoc [`Report`] pointing at `span` in `source`, with
+/// `message` as both its message and the label's text. Any
+/// [`diagnostic!`]-like fields go after the message.
+///
+/// # Examples
+/// ```
+/// use miette::diag;
+///
+/// let report = diag!("let x = ;", 8..9, "expected an expression", help = "add a value");
+/// assert_eq!(report.to_string(), "expected an expression");
+/// assert_eq!(report.help().unwrap().to_string(), "add a value");
+/// ```
+///
+/// [`diagnostic!`]: crate::diagnostic!
+/// [`Report`]: crate::Report
+#[macro_export]
+macro_rules! diag {
+    ($source:expr, $span:expr, $message:expr $(, $key:ident = $value:expr)* $(,)?) => {{
+        let message = ::std::string::ToString::to_string(&$message);
+        let mut diag = $crate::MietteDiagnostic::new(message.clone())
+            .with_label($crate::LabeledSpan::at($span, message));
+        $(diag.$key = Some($value.into());)*
+        $crate::Report::from(diag).with_source_code($source)
+    }};
+}
@@ -0,0 +1,52 @@
+use core::fmt::{self, Debug, Display};
+use std::sync::OnceLock;
+
+use super::Report;
+
+/// A [`Report`] whose rendered output is cached, as returned by
+/// [`Report::memoized`].
+///
+/// The report is rendered by its [`ReportHandler`](crate::ReportHandler) the
+/// first time it's formatted, and both [`Debug`] and [`Display`] return that
+/// same rendering from then on. Since a `Report` captures its handler when
+/// it's created, later changes to the global hook don't affect the output.
+pub struct MemoizedReport {
+    report: Report,
+    rendered: OnceLock<String>,
+}
+
+impl MemoizedReport {
+    pub(crate) fn new(report: Report) -> Self {
+        Self {
+            report,
+            rendered: OnceLock::new(),
+        }
+    }
+
+    /// The rendered report, rendering it first if needed.
+    pub fn rendered(&self) -> &str {
+        self.rendered.get_or_init(|| format!("{:?}", self.report))
+    }
+
+    /// Get a reference to the underlying [`Report`].
+    pub fn report(&self) -> &Report {
+        &self.report
+    }
+
+    /// Unwrap the underlying [`Report`], discarding the cached rendering.
+    pub fn into_report(self) -> Report {
+        self.report
+    }
+}
+
+impl Debug for MemoizedReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.rendered())
+    }
+}
+
+impl Display for MemoizedReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.rendered())
+    }
+}
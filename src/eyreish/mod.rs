@@ -9,8 +9,12 @@ use core::fmt::Display;
 use std::error::Error as StdError;
 use std::sync::OnceLock;
 
+#[allow(unreachable_pub)]
+#[cfg(feature = "fancy-base")]
+pub use colored::ColoredReport;
 #[allow(unreachable_pub)]
 pub use into_diagnostic::*;
+pub use memoized::MemoizedReport;
 #[doc(hidden)]
 #[allow(unreachable_pub)]
 pub use Report as ErrReport;
@@ -34,12 +38,15 @@ use error::ErrorImpl;
 
 use self::ptr::Own;
 
+#[cfg(feature = "fancy-base")]
+mod colored;
 mod context;
 mod error;
 mod fmt;
 mod into_diagnostic;
 mod kind;
 mod macros;
+mod memoized;
 mod ptr;
 mod wrapper;
 
@@ -172,11 +179,7 @@ pub trait ReportHandler: core::any::Any + Send + Sync {
     ///     }
     /// }
     /// ```
-    fn debug(
-        &self,
-        error: &(dyn Diagnostic),
-        f: &mut core::fmt::Formatter<'_>,
-    ) -> core::fmt::Result;
+    fn debug(&self, error: &dyn Diagnostic, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result;
 
     /// Override for the `Display` format
     fn display(
@@ -331,11 +334,11 @@ pub type Result<T, E = Report> = core::result::Result<T, E>;
 /// We encourage you to write this:
 ///
 /// ```rust
-/// use miette::{miette, Report, WrapErr};
+/// use miette::{IntoDiagnostic, Report, WrapErr};
 /// use std::error::Error;
 ///
 /// fn wrap_example(err: Result<(), Box<dyn Error + Send + Sync + 'static>>) -> Result<(), Report> {
-///     err.map_err(|e| miette!(e))
+///     err.into_diagnostic()
 ///         .wrap_err("saw a downstream error")
 /// }
 /// ```
@@ -66,10 +66,22 @@ impl Diagnostic for BoxedError {
         self.0.severity()
     }
 
+    fn is_fatal(&self) -> bool {
+        self.0.is_fatal()
+    }
+
     fn help<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
         self.0.help()
     }
 
+    fn title<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        self.0.title()
+    }
+
+    fn long_message<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        self.0.long_message()
+    }
+
     fn url<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
         self.0.url()
     }
@@ -78,10 +90,18 @@ impl Diagnostic for BoxedError {
         self.0.labels()
     }
 
+    fn context_snippets(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+        self.0.context_snippets()
+    }
+
     fn source_code(&self) -> Option<&dyn miette::SourceCode> {
         self.0.source_code()
     }
 
+    fn source_code_owned(&self) -> Option<Box<dyn miette::SourceCode + '_>> {
+        self.0.source_code_owned()
+    }
+
     fn related<'a>(&'a self) -> Option<Box<dyn Iterator<Item = &'a dyn Diagnostic> + 'a>> {
         self.0.related()
     }
@@ -133,10 +153,22 @@ impl<E: Diagnostic, C: SourceCode> Diagnostic for WithSourceCode<E, C> {
         self.error.severity()
     }
 
+    fn is_fatal(&self) -> bool {
+        self.error.is_fatal()
+    }
+
     fn help<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
         self.error.help()
     }
 
+    fn title<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        self.error.title()
+    }
+
+    fn long_message<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        self.error.long_message()
+    }
+
     fn url<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
         self.error.url()
     }
@@ -145,6 +177,10 @@ impl<E: Diagnostic, C: SourceCode> Diagnostic for WithSourceCode<E, C> {
         self.error.labels()
     }
 
+    fn context_snippets(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+        self.error.context_snippets()
+    }
+
     fn source_code(&self) -> Option<&dyn miette::SourceCode> {
         self.error.source_code().or(Some(&self.source_code))
     }
@@ -167,10 +203,22 @@ impl<C: SourceCode> Diagnostic for WithSourceCode<Report, C> {
         self.error.severity()
     }
 
+    fn is_fatal(&self) -> bool {
+        self.error.is_fatal()
+    }
+
     fn help<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
         self.error.help()
     }
 
+    fn title<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        self.error.title()
+    }
+
+    fn long_message<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        self.error.long_message()
+    }
+
     fn url<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
         self.error.url()
     }
@@ -179,6 +227,10 @@ impl<C: SourceCode> Diagnostic for WithSourceCode<Report, C> {
         self.error.labels()
     }
 
+    fn context_snippets(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+        self.error.context_snippets()
+    }
+
     fn source_code(&self) -> Option<&dyn miette::SourceCode> {
         self.error.source_code().or(Some(&self.source_code))
     }
@@ -216,6 +268,173 @@ impl<C> StdError for WithSourceCode<Report, C> {
     }
 }
 
+pub(crate) struct MapLabels<F> {
+    pub(crate) error: Report,
+    pub(crate) map: F,
+}
+
+impl<F> Diagnostic for MapLabels<F>
+where
+    F: Fn(LabeledSpan) -> LabeledSpan,
+{
+    fn code<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        self.error.code()
+    }
+
+    fn severity(&self) -> Option<miette::Severity> {
+        self.error.severity()
+    }
+
+    fn is_fatal(&self) -> bool {
+        self.error.is_fatal()
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        self.error.help()
+    }
+
+    fn title<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        self.error.title()
+    }
+
+    fn long_message<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        self.error.long_message()
+    }
+
+    fn url<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        self.error.url()
+    }
+
+    fn labels<'a>(&'a self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + 'a>> {
+        let labels = self.error.labels()?;
+        Some(Box::new(labels.map(&self.map)))
+    }
+
+    fn context_snippets(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+        self.error.context_snippets()
+    }
+
+    fn source_code(&self) -> Option<&dyn miette::SourceCode> {
+        self.error.source_code()
+    }
+
+    fn source_code_owned(&self) -> Option<Box<dyn miette::SourceCode + '_>> {
+        self.error.source_code_owned()
+    }
+
+    fn related<'a>(&'a self) -> Option<Box<dyn Iterator<Item = &'a dyn Diagnostic> + 'a>> {
+        self.error.related()
+    }
+
+    fn diagnostic_source(&self) -> Option<&dyn Diagnostic> {
+        self.error.diagnostic_source()
+    }
+}
+
+impl<F> Debug for MapLabels<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Debug::fmt(&self.error, f)
+    }
+}
+
+impl<F> Display for MapLabels<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Display::fmt(&self.error, f)
+    }
+}
+
+impl<F> StdError for MapLabels<F> {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        self.error.source()
+    }
+}
+
+/// The first diagnostic of a tuple converted into a [`Report`], with the
+/// others as its related diagnostics.
+pub(crate) struct AggregateError {
+    pub(crate) primary: Box<dyn Diagnostic + Send + Sync>,
+    pub(crate) related: Vec<Box<dyn Diagnostic + Send + Sync>>,
+}
+
+impl Diagnostic for AggregateError {
+    fn code<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        self.primary.code()
+    }
+
+    fn severity(&self) -> Option<miette::Severity> {
+        self.primary.severity()
+    }
+
+    fn is_fatal(&self) -> bool {
+        self.primary.is_fatal()
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        self.primary.help()
+    }
+
+    fn title<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        self.primary.title()
+    }
+
+    fn long_message<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        self.primary.long_message()
+    }
+
+    fn url<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        self.primary.url()
+    }
+
+    fn labels<'a>(&'a self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + 'a>> {
+        self.primary.labels()
+    }
+
+    fn context_snippets(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+        self.primary.context_snippets()
+    }
+
+    fn source_code(&self) -> Option<&dyn miette::SourceCode> {
+        self.primary.source_code()
+    }
+
+    fn source_code_owned(&self) -> Option<Box<dyn miette::SourceCode + '_>> {
+        self.primary.source_code_owned()
+    }
+
+    fn related<'a>(&'a self) -> Option<Box<dyn Iterator<Item = &'a dyn Diagnostic> + 'a>> {
+        let rest = self.related.iter().map(|rel| &**rel as &'a dyn Diagnostic);
+        Some(match self.primary.related() {
+            Some(own) => Box::new(own.chain(rest)),
+            None => Box::new(rest),
+        })
+    }
+
+    fn diagnostic_source(&self) -> Option<&dyn Diagnostic> {
+        self.primary.diagnostic_source()
+    }
+}
+
+impl Debug for AggregateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("AggregateError")
+            .field(&self.primary)
+            .field(&self.related)
+            .finish()
+    }
+}
+
+impl Display for AggregateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Display::fmt(&self.primary, f)
+    }
+}
+
+impl StdError for AggregateError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        self.primary.source()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use thiserror::Error;
@@ -0,0 +1,149 @@
+use std::{
+    error::Error,
+    fmt::{self, Debug, Display},
+};
+
+use crate::{Diagnostic, LabeledSpan, Severity, SourceCode};
+
+type PropertyFn<T> = Box<dyn Fn() -> T + Send + Sync>;
+
+/// [`Diagnostic`] whose properties are all computed by closures, for adapting
+/// diagnostics from across FFI or plugin boundaries without defining a type
+/// for them.
+///
+/// Properties without a closure fall back to the [`Diagnostic`] defaults.
+///
+/// ```rust
+/// use miette::{FnDiagnostic, LabeledSpan};
+///
+/// let diag = FnDiagnostic::new(|| "oops!".to_string())
+///     .with_code_fn(|| "oops::my::bad".to_string())
+///     .with_labels_fn(|| vec![LabeledSpan::at(0..4, "here")])
+///     .with_source_code("some source");
+/// ```
+pub struct FnDiagnostic {
+    message: PropertyFn<String>,
+    code: Option<PropertyFn<String>>,
+    severity: Option<PropertyFn<Severity>>,
+    help: Option<PropertyFn<String>>,
+    url: Option<PropertyFn<String>>,
+    labels: Option<PropertyFn<Vec<LabeledSpan>>>,
+    source_code: Option<Box<dyn SourceCode>>,
+}
+
+impl FnDiagnostic {
+    /// Create a new `FnDiagnostic` whose message is computed by `message`.
+    pub fn new(message: impl Fn() -> String + Send + Sync + 'static) -> Self {
+        Self {
+            message: Box::new(message),
+            code: None,
+            severity: None,
+            help: None,
+            url: None,
+            labels: None,
+            source_code: None,
+        }
+    }
+
+    /// Computes [`Diagnostic::code`] with `code`.
+    pub fn with_code_fn(mut self, code: impl Fn() -> String + Send + Sync + 'static) -> Self {
+        self.code = Some(Box::new(code));
+        self
+    }
+
+    /// Computes [`Diagnostic::severity`] with `severity`.
+    pub fn with_severity_fn(
+        mut self,
+        severity: impl Fn() -> Severity + Send + Sync + 'static,
+    ) -> Self {
+        self.severity = Some(Box::new(severity));
+        self
+    }
+
+    /// Computes [`Diagnostic::help`] with `help`.
+    pub fn with_help_fn(mut self, help: impl Fn() -> String + Send + Sync + 'static) -> Self {
+        self.help = Some(Box::new(help));
+        self
+    }
+
+    /// Computes [`Diagnostic::url`] with `url`.
+    pub fn with_url_fn(mut self, url: impl Fn() -> String + Send + Sync + 'static) -> Self {
+        self.url = Some(Box::new(url));
+        self
+    }
+
+    /// Computes [`Diagnostic::labels`] with `labels`.
+    pub fn with_labels_fn(
+        mut self,
+        labels: impl Fn() -> Vec<LabeledSpan> + Send + Sync + 'static,
+    ) -> Self {
+        self.labels = Some(Box::new(labels));
+        self
+    }
+
+    /// Sets the [`SourceCode`] the labels point into. Since
+    /// [`Diagnostic::source_code`] returns a reference, this is a value
+    /// rather than a closure.
+    pub fn with_source_code(mut self, source_code: impl SourceCode + 'static) -> Self {
+        self.source_code = Some(Box::new(source_code));
+        self
+    }
+}
+
+impl Debug for FnDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FnDiagnostic")
+            .field("message", &(self.message)())
+            .field("code", &self.code.as_ref().map(|code| code()))
+            .field(
+                "severity",
+                &self.severity.as_ref().map(|severity| severity()),
+            )
+            .field("help", &self.help.as_ref().map(|help| help()))
+            .field("url", &self.url.as_ref().map(|url| url()))
+            .field("labels", &self.labels.as_ref().map(|labels| labels()))
+            .finish_non_exhaustive()
+    }
+}
+
+impl Display for FnDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&(self.message)())
+    }
+}
+
+impl Error for FnDiagnostic {}
+
+impl Diagnostic for FnDiagnostic {
+    fn code<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        self.code
+            .as_ref()
+            .map(|code| Box::new(code()) as Box<dyn Display>)
+    }
+
+    fn severity(&self) -> Option<Severity> {
+        self.severity.as_ref().map(|severity| severity())
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        self.help
+            .as_ref()
+            .map(|help| Box::new(help()) as Box<dyn Display>)
+    }
+
+    fn url<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        self.url
+            .as_ref()
+            .map(|url| Box::new(url()) as Box<dyn Display>)
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+        self.labels
+            .as_ref()
+            .map(|labels| Box::new(labels().into_iter()) as Box<dyn Iterator<Item = LabeledSpan>>)
+    }
+
+    fn source_code(&self) -> Option<&dyn SourceCode> {
+        self.source_code.as_deref()
+    }
+}
@@ -0,0 +1,112 @@
+use crate::{MietteError, SourceCode, SourceSpan, SpanContents};
+
+/**
+[`SourceCode`] that frames another source with synthetic header and footer
+lines (e.g. `// generated`), so that snippets from a fragment read sensibly
+without touching the fragment itself.
+
+Offsets into a `FramedSource` address the header, then the framed source,
+then the footer, so labels pointing into the framed source should be
+shifted past the header; [`FramedSource::framed_span`] does this for you.
+
+```rust
+use miette::{FramedSource, SourceSpan};
+
+let src = FramedSource::new("let x = 1;")
+    .with_header("// generated")
+    .with_footer("// end");
+assert_eq!(src.text(), "// generated\nlet x = 1;\n// end");
+assert_eq!(src.framed_span((4, 1)), SourceSpan::from((17, 1)));
+```
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FramedSource<S> {
+    source: S,
+    header: Option<String>,
+    footer: Option<String>,
+    text: String,
+}
+
+impl<S: AsRef<str>> FramedSource<S> {
+    /// Create a new `FramedSource` around `source`, with no header or
+    /// footer yet.
+    pub fn new(source: S) -> Self {
+        let text = source.as_ref().to_string();
+        Self {
+            source,
+            header: None,
+            footer: None,
+            text,
+        }
+    }
+
+    /// Sets a line to show above the framed source.
+    pub fn with_header(mut self, header: impl Into<String>) -> Self {
+        self.header = Some(header.into());
+        self.reframe();
+        self
+    }
+
+    /// Sets a line to show below the framed source.
+    pub fn with_footer(mut self, footer: impl Into<String>) -> Self {
+        self.footer = Some(footer.into());
+        self.reframe();
+        self
+    }
+
+    fn reframe(&mut self) {
+        let source = self.source.as_ref();
+        let mut text = String::new();
+        if let Some(header) = &self.header {
+            text.push_str(header);
+            text.push('\n');
+        }
+        text.push_str(source);
+        if let Some(footer) = &self.footer {
+            if !source.is_empty() && !source.ends_with('\n') {
+                text.push('\n');
+            }
+            text.push_str(footer);
+        }
+        self.text = text;
+    }
+
+    /// Offset at which the framed source starts.
+    pub fn offset(&self) -> usize {
+        self.header.as_ref().map_or(0, |header| header.len() + 1)
+    }
+
+    /// Converts a span into the framed source into a span into this
+    /// `FramedSource`.
+    pub fn framed_span(&self, span: impl Into<SourceSpan>) -> SourceSpan {
+        let span = span.into();
+        (self.offset() + span.offset(), span.len()).into()
+    }
+
+    /// The full text, including the header and footer.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Returns a reference to the framed source.
+    pub fn inner(&self) -> &S {
+        &self.source
+    }
+
+    /// Unwraps the framed source.
+    pub fn into_inner(self) -> S {
+        self.source
+    }
+}
+
+impl<S: AsRef<str> + Send + Sync> SourceCode for FramedSource<S> {
+    fn read_span<'a>(
+        &'a self,
+        span: &SourceSpan,
+        context_lines_before: usize,
+        context_lines_after: usize,
+    ) -> Result<Box<dyn SpanContents<'a> + 'a>, MietteError> {
+        self.text
+            .read_span(span, context_lines_before, context_lines_after)
+    }
+}
@@ -1,5 +1,6 @@
 use std::fmt;
 
+use crate::handlers::dumb_term;
 use crate::highlighters::Highlighter;
 use crate::highlighters::MietteHighlighter;
 use crate::protocol::Diagnostic;
@@ -42,6 +43,7 @@ miette::set_hook(Box::new(|_| {
 pub struct MietteHandlerOpts {
     pub(crate) linkify: Option<bool>,
     pub(crate) width: Option<usize>,
+    pub(crate) max_width: Option<usize>,
     pub(crate) theme: Option<GraphicalTheme>,
     pub(crate) force_graphical: Option<bool>,
     pub(crate) force_narrated: Option<bool>,
@@ -119,12 +121,20 @@ impl MietteHandlerOpts {
         self
     }
 
-    /// Sets the width to wrap the report at. Defaults to 80.
+    /// Sets the maximum width of the report, which longer lines are wrapped
+    /// at. Shorter lines aren't padded out to it. Defaults to 80.
     pub fn width(mut self, width: usize) -> Self {
         self.width = Some(width);
         self
     }
 
+    /// Caps the width of the report, whether it was set with
+    /// [`width()`](MietteHandlerOpts::width) or detected from the terminal.
+    pub fn max_width(mut self, max_width: usize) -> Self {
+        self.max_width = Some(max_width);
+        self
+    }
+
     /// If true, long lines can be wrapped.
     ///
     /// If false, long lines will not be broken when they exceed the width.
@@ -196,7 +206,8 @@ impl MietteHandlerOpts {
     }
 
     /// If true, forces unicode display for graphical output. If set to false,
-    /// forces ASCII art display.
+    /// forces ASCII art display. By default, unicode is used if the terminal
+    /// supports it and `TERM` isn't `dumb` (or unset).
     pub fn unicode(mut self, unicode: bool) -> Self {
         self.unicode = Some(unicode);
         self
@@ -260,6 +271,7 @@ impl MietteHandlerOpts {
             let characters = match self.unicode {
                 Some(true) => ThemeCharacters::unicode(),
                 Some(false) => ThemeCharacters::ascii(),
+                None if dumb_term() => ThemeCharacters::ascii(),
                 None if syscall::supports_unicode() => ThemeCharacters::unicode(),
                 None => ThemeCharacters::ascii(),
             };
@@ -303,6 +315,9 @@ impl MietteHandlerOpts {
             let mut handler = GraphicalReportHandler::new_themed(theme)
                 .with_width(width)
                 .with_links(linkify);
+            if let Some(max_width) = self.max_width {
+                handler = handler.with_max_width(max_width);
+            }
             handler.highlighter = highlighter;
             if let Some(with_cause_chain) = self.with_cause_chain {
                 if with_cause_chain {
@@ -400,7 +415,7 @@ impl Default for MietteHandler {
 }
 
 impl ReportHandler for MietteHandler {
-    fn debug(&self, diagnostic: &(dyn Diagnostic), f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    fn debug(&self, diagnostic: &dyn Diagnostic, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         if f.alternate() {
             return fmt::Debug::fmt(diagnostic, f);
         }
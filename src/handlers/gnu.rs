@@ -0,0 +1,116 @@
+use std::fmt;
+
+use crate::{
+    handlers::owned_source_code, protocol::Diagnostic, ReportHandler, Severity, SourceCode,
+};
+
+/**
+[`ReportHandler`] that renders one line per label in the GNU
+`file:line:col: severity: message` format, for tools like Vim's quickfix
+list or Emacs' compilation mode that parse this format.
+
+Diagnostics without labels are rendered as `severity: message`. Help text is
+rendered as a separate `note:` line, and related diagnostics are rendered
+after their parent, the same way.
+*/
+#[derive(Debug, Clone)]
+pub struct GnuReportHandler;
+
+impl GnuReportHandler {
+    /// Create a new [`GnuReportHandler`]. There are no customization
+    /// options.
+    pub const fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for GnuReportHandler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GnuReportHandler {
+    /// Render a [`Diagnostic`]. This function is mostly internal and meant to
+    /// be called by the toplevel [`ReportHandler`] handler, but is made public
+    /// to make it easier (possible) to test in isolation from global state.
+    pub fn render_report(
+        &self,
+        f: &mut impl fmt::Write,
+        diagnostic: &dyn Diagnostic,
+    ) -> fmt::Result {
+        self.render_report_inner(f, diagnostic, None)
+    }
+
+    fn render_report_inner(
+        &self,
+        f: &mut impl fmt::Write,
+        diagnostic: &dyn Diagnostic,
+        parent_src: Option<&dyn SourceCode>,
+    ) -> fmt::Result {
+        let owned_src = owned_source_code(diagnostic);
+        let src = diagnostic
+            .source_code()
+            .or(owned_src.as_deref())
+            .or(parent_src);
+        let severity = severity_name(diagnostic);
+        let locations = label_locations(diagnostic, src);
+
+        if locations.is_empty() {
+            writeln!(f, "{}: {}", severity, diagnostic)?;
+        }
+        for location in &locations {
+            writeln!(f, "{}{}: {}", location, severity, diagnostic)?;
+        }
+        if let Some(help) = diagnostic.help() {
+            let location = locations.first().map(String::as_str).unwrap_or("");
+            writeln!(f, "{}note: {}", location, help)?;
+        }
+
+        if let Some(related) = diagnostic.related() {
+            for rel in related {
+                self.render_report_inner(f, rel, src)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Lowercase name of `diagnostic`'s severity, as used by compilers.
+pub(super) fn severity_name(diagnostic: &dyn Diagnostic) -> &'static str {
+    match diagnostic.severity() {
+        Some(Severity::Error) | None => "error",
+        Some(Severity::Warning) => "warning",
+        Some(Severity::Advice) => "note",
+    }
+}
+
+/// `file:line:col: ` prefixes for each of `diagnostic`'s labels that can be
+/// read from `src`, primary labels first and then in source order.
+pub(super) fn label_locations(
+    diagnostic: &dyn Diagnostic,
+    src: Option<&dyn SourceCode>,
+) -> Vec<String> {
+    let mut locations = Vec::new();
+    if let (Some(src), Some(labels)) = (src, diagnostic.labels()) {
+        let mut labels = labels.collect::<Vec<_>>();
+        labels.sort_by_key(|label| (!label.primary(), label.offset()));
+        for label in labels {
+            if let Ok(contents) = src.read_span(label.inner(), 0, 0) {
+                locations.push(format!(
+                    "{}:{}:{}: ",
+                    contents.name().unwrap_or("<unknown>"),
+                    contents.line() + 1,
+                    contents.column() + 1
+                ));
+            }
+        }
+    }
+    locations
+}
+
+impl ReportHandler for GnuReportHandler {
+    fn debug(&self, diagnostic: &dyn Diagnostic, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.render_report(f, diagnostic)
+    }
+}
@@ -1,13 +1,21 @@
+use std::borrow::Cow;
+use std::collections::BTreeMap;
 use std::fmt::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
-use owo_colors::{OwoColorize, Style, StyledList};
-use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+use owo_colors::{OwoColorize, Style, Styled, StyledList};
+use unicode_width::UnicodeWidthChar;
 
 use crate::diagnostic_chain::{DiagnosticChain, ErrorKind};
+use crate::handlers::owned_source_code;
 use crate::handlers::theme::*;
 use crate::highlighters::{Highlighter, MietteHighlighter};
 use crate::protocol::{Diagnostic, Severity};
-use crate::{LabeledSpan, ReportHandler, SourceCode, SourceSpan, SpanContents};
+use crate::{
+    DiffSource, LabelRef, LabelStyle, LabeledSpan, ReportHandler, SourceCode, SourceSpan,
+    SpanContents,
+};
 
 /**
 A [`ReportHandler`] that displays a given [`Report`](crate::Report) in a
@@ -28,8 +36,9 @@ pub struct GraphicalReportHandler {
     pub(crate) termwidth: usize,
     pub(crate) theme: GraphicalTheme,
     pub(crate) footer: Option<String>,
+    pub(crate) prefix: Option<String>,
     pub(crate) context_lines: usize,
-    pub(crate) tab_width: usize,
+    pub(crate) tab_width: Option<usize>,
     pub(crate) with_cause_chain: bool,
     pub(crate) wrap_lines: bool,
     pub(crate) break_words: bool,
@@ -37,6 +46,63 @@ pub struct GraphicalReportHandler {
     pub(crate) word_splitter: Option<textwrap::WordSplitter>,
     pub(crate) highlighter: MietteHighlighter,
     pub(crate) link_display_text: Option<String>,
+    pub(crate) group_related_by_file: bool,
+    pub(crate) classic: bool,
+    pub(crate) related_indent: usize,
+    pub(crate) render_cause_codes: bool,
+    pub(crate) related_spacing: usize,
+    pub(crate) render_related_header: bool,
+    pub(crate) bold_message: bool,
+    pub(crate) collapse_empty_source: bool,
+    pub(crate) path_base: Option<PathBuf>,
+    pub(crate) gutter_separator: Option<char>,
+    pub(crate) gutter_align: GutterAlign,
+    pub(crate) compact: bool,
+    pub(crate) sections: RenderSections,
+    pub(crate) max_width: Option<usize>,
+    pub(crate) label_legend: bool,
+    pub(crate) numbered_labels: bool,
+    pub(crate) empty_span_marker: Option<char>,
+    pub(crate) always_show_header: bool,
+    pub(crate) help_position: HelpPosition,
+    pub(crate) severity_in_header: bool,
+    pub(crate) diff_view: bool,
+    pub(crate) snippet_caption: bool,
+    pub(crate) indent: usize,
+    pub(crate) default_help: BTreeMap<Severity, String>,
+    pub(crate) show_whitespace: bool,
+    pub(crate) related_separator: Option<String>,
+    pub(crate) context_merge_gap: usize,
+    pub(crate) show_missing_source: bool,
+    pub(crate) show_columns: bool,
+    pub(crate) text_severity: bool,
+    pub(crate) related_count_in_header: bool,
+    pub(crate) inline_label_threshold: Option<usize>,
+    pub(crate) trim_trailing_whitespace: bool,
+    pub(crate) related_first: bool,
+    pub(crate) focus: Option<SourceSpan>,
+    pub(crate) severity_from_code: Option<SeverityFromCode>,
+    pub(crate) render_order: Option<Vec<Section>>,
+    pub(crate) error_style: Option<Style>,
+    pub(crate) warning_style: Option<Style>,
+    pub(crate) advice_style: Option<Style>,
+    pub(crate) deletion_style: Option<Style>,
+    pub(crate) dimmed_style: Option<Style>,
+    pub(crate) primary_style: Option<Style>,
+    pub(crate) secondary_underline: Option<char>,
+}
+
+type SeverityFn = dyn Fn(&str) -> Option<Severity> + Send + Sync;
+
+/// Mapping from a diagnostic's code to its severity, set with
+/// [`GraphicalReportHandler::with_severity_from_code`].
+#[derive(Clone)]
+pub(crate) struct SeverityFromCode(Arc<SeverityFn>);
+
+impl fmt::Debug for SeverityFromCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SeverityFromCode")
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -46,6 +112,117 @@ pub(crate) enum LinkStyle {
     Text,
 }
 
+/// Alignment of the line numbers in the gutter of a
+/// [`GraphicalReportHandler`] snippet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GutterAlign {
+    /// Line numbers are aligned to the left edge of the gutter.
+    Left,
+    /// Line numbers are aligned to the separator. This is the default.
+    #[default]
+    Right,
+}
+
+/// Where a [`GraphicalReportHandler`] renders a diagnostic's help text,
+/// relative to its source snippets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HelpPosition {
+    /// Help is rendered above the snippets, so the suggested fix is read
+    /// first.
+    BeforeSnippet,
+    /// Help is rendered below the snippets. This is the default.
+    #[default]
+    AfterSnippet,
+}
+
+/// A section of a report rendered by a [`GraphicalReportHandler`], for
+/// [`GraphicalReportHandler::with_render_order`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Section {
+    /// The header line, with the diagnostic's code and link.
+    Header,
+    /// The diagnostic's `×`-prefixed message.
+    Message,
+    /// The cause chain below the diagnostic's message.
+    Causes,
+    /// Source code snippets with the diagnostic's labels.
+    Snippets,
+    /// The diagnostic's help text.
+    Help,
+    /// The diagnostic's related diagnostics.
+    Related,
+    /// The footer set with [`GraphicalReportHandler::with_footer`].
+    Footer,
+}
+
+/// Set of report sections for a [`GraphicalReportHandler`] to render (see
+/// [`GraphicalReportHandler::with_sections`]). Sections can be combined with
+/// `|`, e.g. `RenderSections::HEADER | RenderSections::SNIPPETS`.
+///
+/// The diagnostic's `×`-prefixed message is always rendered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RenderSections(u8);
+
+impl RenderSections {
+    /// No optional sections, just the diagnostic's message.
+    pub const NONE: Self = Self(0);
+    /// The header line, with the diagnostic's code and link.
+    pub const HEADER: Self = Self(1);
+    /// The cause chain below the diagnostic's message.
+    pub const CAUSES: Self = Self(1 << 1);
+    /// Source code snippets with the diagnostic's labels.
+    pub const SNIPPETS: Self = Self(1 << 2);
+    /// The diagnostic's help text.
+    pub const HELP: Self = Self(1 << 3);
+    /// The footer set with [`GraphicalReportHandler::with_footer`].
+    pub const FOOTER: Self = Self(1 << 4);
+    /// Related diagnostics.
+    pub const RELATED: Self = Self(1 << 5);
+    /// All sections. This is the default.
+    pub const ALL: Self = Self(0b11_1111);
+
+    /// Whether all of the sections in `other` are also in `self`.
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl Default for RenderSections {
+    fn default() -> Self {
+        Self::ALL
+    }
+}
+
+impl std::ops::BitOr for RenderSections {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl std::ops::BitOrAssign for RenderSections {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0;
+    }
+}
+
+impl std::ops::BitAnd for RenderSections {
+    type Output = Self;
+
+    fn bitand(self, rhs: Self) -> Self {
+        Self(self.0 & rhs.0)
+    }
+}
+
+impl std::ops::Not for RenderSections {
+    type Output = Self;
+
+    fn not(self) -> Self {
+        Self(!self.0 & Self::ALL.0)
+    }
+}
+
 impl GraphicalReportHandler {
     /// Create a new `GraphicalReportHandler` with the default
     /// [`GraphicalTheme`]. This will use both unicode characters and colors.
@@ -55,8 +232,9 @@ impl GraphicalReportHandler {
             termwidth: 200,
             theme: GraphicalTheme::default(),
             footer: None,
+            prefix: None,
             context_lines: 1,
-            tab_width: 4,
+            tab_width: None,
             with_cause_chain: true,
             wrap_lines: true,
             break_words: true,
@@ -64,6 +242,50 @@ impl GraphicalReportHandler {
             word_splitter: None,
             highlighter: MietteHighlighter::default(),
             link_display_text: None,
+            group_related_by_file: false,
+            classic: false,
+            related_indent: 0,
+            render_cause_codes: false,
+            related_spacing: 1,
+            render_related_header: true,
+            bold_message: false,
+            collapse_empty_source: false,
+            path_base: None,
+            gutter_separator: None,
+            gutter_align: GutterAlign::Right,
+            compact: false,
+            sections: RenderSections::ALL,
+            max_width: None,
+            label_legend: false,
+            numbered_labels: false,
+            empty_span_marker: None,
+            always_show_header: false,
+            help_position: HelpPosition::AfterSnippet,
+            severity_in_header: false,
+            diff_view: false,
+            snippet_caption: false,
+            indent: 0,
+            default_help: BTreeMap::new(),
+            show_whitespace: false,
+            related_separator: None,
+            context_merge_gap: 0,
+            show_missing_source: false,
+            show_columns: false,
+            text_severity: false,
+            related_count_in_header: false,
+            inline_label_threshold: None,
+            trim_trailing_whitespace: false,
+            related_first: false,
+            focus: None,
+            severity_from_code: None,
+            render_order: None,
+            error_style: None,
+            warning_style: None,
+            advice_style: None,
+            deletion_style: None,
+            dimmed_style: None,
+            primary_style: None,
+            secondary_underline: None,
         }
     }
 
@@ -74,8 +296,9 @@ impl GraphicalReportHandler {
             termwidth: 200,
             theme,
             footer: None,
+            prefix: None,
             context_lines: 1,
-            tab_width: 4,
+            tab_width: None,
             wrap_lines: true,
             with_cause_chain: true,
             break_words: true,
@@ -83,12 +306,58 @@ impl GraphicalReportHandler {
             word_splitter: None,
             highlighter: MietteHighlighter::default(),
             link_display_text: None,
+            group_related_by_file: false,
+            classic: false,
+            related_indent: 0,
+            render_cause_codes: false,
+            related_spacing: 1,
+            render_related_header: true,
+            bold_message: false,
+            collapse_empty_source: false,
+            path_base: None,
+            gutter_separator: None,
+            gutter_align: GutterAlign::Right,
+            compact: false,
+            sections: RenderSections::ALL,
+            max_width: None,
+            label_legend: false,
+            numbered_labels: false,
+            empty_span_marker: None,
+            always_show_header: false,
+            help_position: HelpPosition::AfterSnippet,
+            severity_in_header: false,
+            diff_view: false,
+            snippet_caption: false,
+            indent: 0,
+            default_help: BTreeMap::new(),
+            show_whitespace: false,
+            related_separator: None,
+            context_merge_gap: 0,
+            show_missing_source: false,
+            show_columns: false,
+            text_severity: false,
+            related_count_in_header: false,
+            inline_label_threshold: None,
+            trim_trailing_whitespace: false,
+            related_first: false,
+            focus: None,
+            severity_from_code: None,
+            render_order: None,
+            error_style: None,
+            warning_style: None,
+            advice_style: None,
+            deletion_style: None,
+            dimmed_style: None,
+            primary_style: None,
+            secondary_underline: None,
         }
     }
 
-    /// Set the displayed tab width in spaces.
+    /// Set the displayed tab width in spaces. If this isn't set, the tab
+    /// width reported by the source's [`SpanContents::tab_width`] is used,
+    /// or `4` if it doesn't report one.
     pub fn tab_width(mut self, width: usize) -> Self {
-        self.tab_width = width;
+        self.tab_width = Some(width);
         self
     }
 
@@ -116,6 +385,33 @@ impl GraphicalReportHandler {
         self
     }
 
+    /// Whether to render the cause chain of the diagnostic, for when it's
+    /// already been logged elsewhere. Shorthand for adding or removing
+    /// [`RenderSections::CAUSES`] with
+    /// [`GraphicalReportHandler::with_sections`]. Defaults to `true`.
+    pub fn with_render_causes(mut self, render_causes: bool) -> Self {
+        if render_causes {
+            self.sections |= RenderSections::CAUSES;
+        } else {
+            self.sections = self.sections & !RenderSections::CAUSES;
+        }
+        self
+    }
+
+    /// Whether to render source code snippets with the diagnostic's labels.
+    /// Turning this off keeps the header, message, causes and help, for when
+    /// the source isn't interesting. Shorthand for adding or removing
+    /// [`RenderSections::SNIPPETS`] with
+    /// [`GraphicalReportHandler::with_sections`]. Defaults to `true`.
+    pub fn with_render_snippets(mut self, render_snippets: bool) -> Self {
+        if render_snippets {
+            self.sections |= RenderSections::SNIPPETS;
+        } else {
+            self.sections = self.sections & !RenderSections::SNIPPETS;
+        }
+        self
+    }
+
     /// Whether to include [`Diagnostic::url()`] in the output.
     ///
     /// Disabling this is not recommended, but can be useful for more easily
@@ -135,9 +431,30 @@ impl GraphicalReportHandler {
         self
     }
 
-    /// Sets the width to wrap the report at.
+    /// Override the styles used for each [`Severity`] in the report header
+    /// and cause chain, keeping the rest of the theme. The overrides are kept
+    /// even if the theme is changed afterwards.
+    pub fn with_severity_colors(mut self, error: Style, warning: Style, advice: Style) -> Self {
+        self.error_style = Some(error);
+        self.warning_style = Some(warning);
+        self.advice_style = Some(advice);
+        self
+    }
+
+    /// Sets the maximum width of the report. Text that would go past it is
+    /// wrapped (see [`GraphicalReportHandler::with_wrap_lines`]), but lines
+    /// are never padded out to it, so shorter reports stay narrower.
     pub fn with_width(mut self, width: usize) -> Self {
-        self.termwidth = width;
+        self.termwidth = self.max_width.map_or(width, |max| width.min(max));
+        self
+    }
+
+    /// Caps the width of the report, including any width set later through
+    /// [`GraphicalReportHandler::with_width`]. Useful when the width comes
+    /// from the terminal, since very wide reports are hard to read.
+    pub fn with_max_width(mut self, max_width: usize) -> Self {
+        self.max_width = Some(max_width);
+        self.termwidth = self.termwidth.min(max_width);
         self
     }
 
@@ -171,6 +488,14 @@ impl GraphicalReportHandler {
         self
     }
 
+    /// Sets a prefix, such as a banner with the application's name, to be
+    /// printed as-is at the start of every report. Unlike the footer, it is
+    /// not wrapped.
+    pub fn with_prefix(mut self, prefix: String) -> Self {
+        self.prefix = Some(prefix);
+        self
+    }
+
     /// Sets the number of lines of context to show around each error.
     pub fn with_context_lines(mut self, lines: usize) -> Self {
         self.context_lines = lines;
@@ -201,6 +526,344 @@ impl GraphicalReportHandler {
         self.link_display_text = Some(text.into());
         self
     }
+
+    /// Whether to group related diagnostics by the name of their source,
+    /// rendering each file's diagnostics together under a sub-heading.
+    /// Defaults to `false`, which renders related diagnostics in the order
+    /// they were provided.
+    pub fn with_group_related_by_file(mut self, group: bool) -> Self {
+        self.group_related_by_file = group;
+        self
+    }
+
+    /// Render snippets in a "classic compiler" layout instead of drawing a
+    /// box around them: each label is rendered as a `file:line:col: message`
+    /// line, followed by the source line it points to and a `^^^` underline.
+    pub fn with_classic(mut self, classic: bool) -> Self {
+        self.classic = classic;
+        self
+    }
+
+    /// Set the number of spaces each level of nested related diagnostics is
+    /// indented by, relative to the diagnostic it's related to. Defaults to
+    /// `0`, which renders all related diagnostics flat.
+    pub fn with_related_indent(mut self, indent: usize) -> Self {
+        self.related_indent = indent;
+        self
+    }
+
+    /// Set the number of blank lines printed before each related diagnostic.
+    /// Defaults to `1`.
+    pub fn with_related_spacing(mut self, spacing: usize) -> Self {
+        self.related_spacing = spacing;
+        self
+    }
+
+    /// Sets the maximum number of lines that may separate the contexts of two
+    /// labels for them to still be rendered in a single box, with the lines
+    /// in between filled in. Defaults to `0`, so only touching or
+    /// overlapping contexts are merged.
+    pub fn with_context_merge_gap(mut self, gap: usize) -> Self {
+        self.context_merge_gap = gap;
+        self
+    }
+
+    /// Whether to list the labels of diagnostics that have no source code to
+    /// render them against, as `[no source available for label 'x' at
+    /// 9..13]`, instead of silently leaving them out. Useful for tracking
+    /// down missing source code. Defaults to `false`.
+    pub fn with_show_missing_source(mut self, show: bool) -> Self {
+        self.show_missing_source = show;
+        self
+    }
+
+    /// Whether to show the column of the first label starting on a line
+    /// next to its line number in the gutter, as `line:col`. Defaults to
+    /// `false`.
+    pub fn with_show_columns(mut self, show_columns: bool) -> Self {
+        self.show_columns = show_columns;
+        self
+    }
+
+    /// Whether to write the severity as a (colored) `error:`, `warning:` or
+    /// `advice:` before the diagnostic message, instead of the theme's
+    /// glyph, for fonts that can't display those glyphs. Defaults to
+    /// `false`.
+    pub fn with_text_severity(mut self, text_severity: bool) -> Self {
+        self.text_severity = text_severity;
+        self
+    }
+
+    /// Whether to add the number of related diagnostics to the header, as
+    /// `(+3 related)`, for diagnostics that have any. Defaults to `false`.
+    pub fn with_related_count_in_header(mut self, related_count: bool) -> Self {
+        self.related_count_in_header = related_count;
+        self
+    }
+
+    /// Sets the maximum number of labels on a single line that get their own
+    /// connector rows. Lines with more labels than this get their
+    /// underlines numbered instead, with the label texts listed below as
+    /// `[1] text`. Defaults to no limit.
+    pub fn with_inline_label_threshold(mut self, threshold: usize) -> Self {
+        self.inline_label_threshold = Some(threshold);
+        self
+    }
+
+    /// Whether to trim trailing whitespace from every rendered line, such as
+    /// the padding after gutters on otherwise empty lines, which shows up as
+    /// noise in diffs and some editors. Defaults to `false`.
+    pub fn with_trim_trailing_whitespace(mut self, trim: bool) -> Self {
+        self.trim_trailing_whitespace = trim;
+        self
+    }
+
+    /// Render a diagnostic's [related](Diagnostic::related) diagnostics right
+    /// after its message and causes, before its snippets and help, instead of
+    /// after them. Defaults to `false`.
+    pub fn with_related_first(mut self, related_first: bool) -> Self {
+        self.related_first = related_first;
+        self
+    }
+
+    /// Sets the style for the lines and text of
+    /// [primary labels](crate::LabeledSpan::primary), setting them apart from
+    /// the others, which keep cycling through the theme's highlight styles.
+    /// By default, primary labels cycle through them too.
+    pub fn with_primary_style(mut self, style: Style) -> Self {
+        self.primary_style = Some(style);
+        self
+    }
+
+    /// Sets the underline character for labels that aren't
+    /// [primary](crate::LabeledSpan::primary), e.g. `'╌'` to set them apart
+    /// from primary ones. Defaults to the theme's
+    /// [`underline`](crate::ThemeCharacters::underline).
+    pub fn with_secondary_underline(mut self, underline: char) -> Self {
+        self.secondary_underline = Some(underline);
+        self
+    }
+
+    /// Sets the style for source text covered by
+    /// [deletion labels](crate::LabelStyle::Deletion). Defaults to
+    /// strikethrough, unless the theme doesn't style anything (like
+    /// [`GraphicalTheme::none`]).
+    pub fn with_deletion_style(mut self, style: Style) -> Self {
+        self.deletion_style = Some(style);
+        self
+    }
+
+    /// Dims all source text outside of `focus` in rendered snippets (see
+    /// [`GraphicalReportHandler::with_dimmed_style`]), to draw attention to
+    /// that region.
+    pub fn with_focus(mut self, focus: impl Into<SourceSpan>) -> Self {
+        self.focus = Some(focus.into());
+        self
+    }
+
+    /// Sets the style for source text outside of the span set with
+    /// [`GraphicalReportHandler::with_focus`]. Defaults to dimmed, unless the
+    /// theme doesn't style anything (like [`GraphicalTheme::none`]).
+    pub fn with_dimmed_style(mut self, style: Style) -> Self {
+        self.dimmed_style = Some(style);
+        self
+    }
+
+    /// Derive the severity of diagnostics that don't specify one from their
+    /// [code](Diagnostic::code), e.g. to render codes starting with `warn::`
+    /// as warnings. Diagnostics that `mapper` returns `None` for, or that
+    /// have no code, are still rendered as errors.
+    pub fn with_severity_from_code(
+        mut self,
+        mapper: impl Fn(&str) -> Option<Severity> + Send + Sync + 'static,
+    ) -> Self {
+        self.severity_from_code = Some(SeverityFromCode(Arc::new(mapper)));
+        self
+    }
+
+    /// Render the sections of each diagnostic, including related ones, in
+    /// the given order, leaving out any that aren't listed. This takes
+    /// precedence over [`GraphicalReportHandler::with_help_position`] and
+    /// [`GraphicalReportHandler::with_related_first`]. Sections turned off
+    /// with [`GraphicalReportHandler::with_sections`] are still left out.
+    pub fn with_render_order(mut self, order: &[Section]) -> Self {
+        self.render_order = Some(order.to_vec());
+        self
+    }
+
+    /// Sets a line to print between consecutive related diagnostics, such as
+    /// a `───` rule. It's printed before the blank lines that usually
+    /// separate them. Defaults to none.
+    pub fn with_related_separator(mut self, separator: impl Into<String>) -> Self {
+        self.related_separator = Some(separator.into());
+        self
+    }
+
+    /// Whether to separate related diagnostics from what comes before them
+    /// with blank lines (see
+    /// [`GraphicalReportHandler::with_related_spacing`]) and an `Error:`,
+    /// `Warning:` or `Advice:` prefix. Defaults to `true`. When `false`,
+    /// related diagnostics directly follow their parent.
+    pub fn with_render_related_header(mut self, render: bool) -> Self {
+        self.render_related_header = render;
+        self
+    }
+
+    /// Whether to render the `×`-prefixed diagnostic message in bold, in
+    /// addition to its severity's color. Defaults to `false`.
+    pub fn with_bold_message(mut self, bold: bool) -> Self {
+        self.bold_message = bold;
+        self
+    }
+
+    /// Whether to replace the (otherwise empty) snippet box of a diagnostic
+    /// whose source code is empty with a short `(empty source: name)` note.
+    /// Defaults to `false`.
+    pub fn with_collapse_empty_source(mut self, collapse: bool) -> Self {
+        self.collapse_empty_source = collapse;
+        self
+    }
+
+    /// Render source names relative to `base` when they point to a file
+    /// under it, e.g. `src/main.rs` instead of `/home/me/project/src/main.rs`.
+    /// Source names outside of `base` are rendered unchanged.
+    pub fn with_path_base(mut self, base: PathBuf) -> Self {
+        self.path_base = Some(base);
+        self
+    }
+
+    /// Set the character separating line numbers from the source text in
+    /// snippets. Defaults to the theme's
+    /// [`vbar`](crate::ThemeCharacters::vbar).
+    pub fn with_gutter_separator(mut self, separator: char) -> Self {
+        self.gutter_separator = Some(separator);
+        self
+    }
+
+    /// Set how line numbers are aligned in the gutter. Defaults to
+    /// [`GutterAlign::Right`].
+    pub fn with_gutter_align(mut self, align: GutterAlign) -> Self {
+        self.gutter_align = align;
+        self
+    }
+
+    /// Set whether help text is rendered before or after the source
+    /// snippets. Defaults to [`HelpPosition::AfterSnippet`].
+    pub fn with_help_position(mut self, position: HelpPosition) -> Self {
+        self.help_position = position;
+        self
+    }
+
+    /// Render each diagnostic as a single line with its
+    /// [`Diagnostic::title`] (or its message, if it has no title), leaving
+    /// out everything else. Useful for lists of diagnostics. Defaults to
+    /// `false`.
+    pub fn with_compact(mut self, compact: bool) -> Self {
+        self.compact = compact;
+        self
+    }
+
+    /// Set which sections of the report to render. Defaults to
+    /// [`RenderSections::ALL`].
+    ///
+    /// ```rust
+    /// use miette::{GraphicalReportHandler, RenderSections};
+    ///
+    /// // Everything but the help text.
+    /// let handler = GraphicalReportHandler::new().with_sections(!RenderSections::HELP);
+    /// ```
+    pub fn with_sections(mut self, sections: RenderSections) -> Self {
+        self.sections = sections;
+        self
+    }
+
+    /// Whether to print a legend after the snippets, with a swatch in each
+    /// label's color next to its text. Handy when a diagnostic has enough
+    /// labels that they're hard to tell apart. Defaults to `false`.
+    pub fn with_label_legend(mut self, label_legend: bool) -> Self {
+        self.label_legend = label_legend;
+        self
+    }
+
+    /// Whether to prefix the text of each label with its number, like `[1]`,
+    /// so the diagnostic's message can refer to it with a
+    /// [`LabelRef`](crate::LabelRef). Defaults to `false`.
+    pub fn with_numbered_labels(mut self, numbered_labels: bool) -> Self {
+        self.numbered_labels = numbered_labels;
+        self
+    }
+
+    /// Sets the character that marks zero-length labels, e.g. `'‸'` or
+    /// `'▏'`. Defaults to the theme's `uarrow` character.
+    pub fn with_empty_span_marker(mut self, marker: char) -> Self {
+        self.empty_span_marker = Some(marker);
+        self
+    }
+
+    /// Whether to render a header line for diagnostics without a code (or,
+    /// with links enabled, a URL). The line then holds just the severity, so
+    /// every diagnostic in a batch starts the same way. Defaults to `false`.
+    pub fn with_always_show_header(mut self, always_show_header: bool) -> Self {
+        self.always_show_header = always_show_header;
+        self
+    }
+
+    /// Whether to prefix the header's diagnostic code with the severity,
+    /// rustc-style, e.g. `error[oops::my::bad]`. Defaults to `false`.
+    pub fn with_severity_in_header(mut self, severity_in_header: bool) -> Self {
+        self.severity_in_header = severity_in_header;
+        self
+    }
+
+    /// Whether to render [`DiffSource`]s as two columns, with the text
+    /// before the change on the left and the text after it on the right,
+    /// each with its own labels. Defaults to `false`.
+    pub fn with_diff_view(mut self, diff_view: bool) -> Self {
+        self.diff_view = diff_view;
+        self
+    }
+
+    /// Whether to repeat the text of each snippet's primary label as a
+    /// caption after the snippet's `╭─[file:line:col]` header. Useful for
+    /// diagnostics with a single dominant label. Defaults to `false`.
+    pub fn with_render_snippet_caption(mut self, snippet_caption: bool) -> Self {
+        self.snippet_caption = snippet_caption;
+        self
+    }
+
+    /// Indents every line of the output by `indent` spaces, for embedding
+    /// diagnostics inside other output. The width available for wrapping is
+    /// reduced to match. Blank lines are left empty. Defaults to `0`.
+    pub fn with_indent(mut self, indent: usize) -> Self {
+        self.indent = indent;
+        self
+    }
+
+    /// Sets help text to render for diagnostics of the given severity that
+    /// don't have any help of their own, e.g. `"This is a suggestion; no
+    /// action required."` for [`Severity::Advice`]. Diagnostics without a
+    /// severity count as errors.
+    pub fn with_default_help(mut self, severity: Severity, help: impl Into<String>) -> Self {
+        self.default_help.insert(severity, help.into());
+        self
+    }
+
+    /// Whether to show the codes of errors in the cause chain, as
+    /// `[code] message`, for causes that are [`Diagnostic`]s with a code.
+    /// Defaults to `false`.
+    pub fn with_render_cause_codes(mut self, render: bool) -> Self {
+        self.render_cause_codes = render;
+        self
+    }
+
+    /// Whether to make whitespace inside labeled spans visible, rendering
+    /// spaces as `·` and tabs as `→`, so that errors about trailing
+    /// whitespace or mixed indentation have something to point at. Defaults
+    /// to `false`.
+    pub fn with_show_whitespace(mut self, show_whitespace: bool) -> Self {
+        self.show_whitespace = show_whitespace;
+        self
+    }
 }
 
 impl Default for GraphicalReportHandler {
@@ -216,24 +879,166 @@ impl GraphicalReportHandler {
     pub fn render_report(
         &self,
         f: &mut impl fmt::Write,
-        diagnostic: &(dyn Diagnostic),
+        diagnostic: &dyn Diagnostic,
     ) -> fmt::Result {
-        self.render_report_inner(f, diagnostic, diagnostic.source_code())
+        if self.trim_trailing_whitespace {
+            let mut renderer = self.clone();
+            renderer.trim_trailing_whitespace = false;
+            let mut out = String::new();
+            renderer.render_report(&mut out, diagnostic)?;
+            for line in out.split_inclusive('\n') {
+                match line.strip_suffix('\n') {
+                    Some(line) => writeln!(f, "{}", line.trim_end_matches([' ', '\t']))?,
+                    None => f.write_str(line.trim_end_matches([' ', '\t']))?,
+                }
+            }
+            return Ok(());
+        }
+        if self.indent > 0 {
+            let mut renderer = self.clone();
+            renderer.indent = 0;
+            renderer.termwidth = renderer.termwidth.saturating_sub(self.indent);
+            let mut f = IndentWriter {
+                inner: f,
+                indent: " ".repeat(self.indent),
+                at_line_start: true,
+            };
+            return renderer.render_report_unindented(&mut f, diagnostic);
+        }
+        self.render_report_unindented(f, diagnostic)
+    }
+
+    fn render_report_unindented(
+        &self,
+        f: &mut impl fmt::Write,
+        diagnostic: &dyn Diagnostic,
+    ) -> fmt::Result {
+        if let Some(prefix) = &self.prefix {
+            writeln!(f, "{}", prefix)?;
+        }
+        self.render_report_inner(f, diagnostic, None, diagnostic.source_code())
     }
 
+    /// Render just the source snippet for one of `diagnostic`'s labels,
+    /// picked by its index in [`Diagnostic::labels`]. This is useful for UIs
+    /// that reveal a diagnostic's labels one at a time.
+    ///
+    /// Returns an error if there's no label at `label_index`, or if the
+    /// diagnostic has no source code to render it from.
+    pub fn render_label_context(
+        &self,
+        f: &mut impl fmt::Write,
+        diagnostic: &dyn Diagnostic,
+        label_index: usize,
+    ) -> fmt::Result {
+        let owned_src = owned_source_code(diagnostic);
+        let source = diagnostic
+            .source_code()
+            .or(owned_src.as_deref())
+            .ok_or(fmt::Error)?;
+        let mut label = diagnostic
+            .labels()
+            .and_then(|mut labels| labels.nth(label_index))
+            .ok_or(fmt::Error)?;
+        if self.numbered_labels {
+            if let Some(text) = label.label() {
+                let text = format!("{} {}", LabelRef(label_index + 1), text);
+                label.set_label(Some(text));
+            }
+        }
+        self.render_context(f, source, &label, std::slice::from_ref(&label))
+    }
+
+    /// Renders `diagnostic`. If `message` is given, it replaces both the
+    /// header and the diagnostic's own message.
     fn render_report_inner(
         &self,
         f: &mut impl fmt::Write,
-        diagnostic: &(dyn Diagnostic),
+        diagnostic: &dyn Diagnostic,
+        message: Option<&str>,
         parent_src: Option<&dyn SourceCode>,
     ) -> fmt::Result {
-        let src = diagnostic.source_code().or(parent_src);
-        self.render_header(f, diagnostic)?;
-        self.render_causes(f, diagnostic, src)?;
-        self.render_snippets(f, diagnostic, src)?;
-        self.render_footer(f, diagnostic)?;
-        self.render_related(f, diagnostic, src)?;
-        if let Some(footer) = &self.footer {
+        let owned_src = owned_source_code(diagnostic);
+        let src = diagnostic
+            .source_code()
+            .or(owned_src.as_deref())
+            .or(parent_src);
+        let with_header = message.is_none();
+        let message = message
+            .map(String::from)
+            .unwrap_or_else(|| diagnostic.to_string());
+        if self.compact {
+            let title = diagnostic
+                .title()
+                .map(|title| title.to_string())
+                .unwrap_or_else(|| diagnostic.to_string());
+            let mut renderer = self.clone();
+            renderer.with_cause_chain = false;
+            return renderer.render_causes_with_message(f, diagnostic, &title, src);
+        }
+        for section in self.render_order().iter() {
+            match section {
+                Section::Header if with_header => self.render_header(f, diagnostic)?,
+                Section::Related => self.render_related(f, diagnostic, src)?,
+                Section::Footer => self.render_handler_footer(f)?,
+                _ => self.render_body_section(f, *section, diagnostic, &message, src)?,
+            }
+        }
+        Ok(())
+    }
+
+    /// The order to render each diagnostic's sections in: the one set with
+    /// [`GraphicalReportHandler::with_render_order`], or else the default
+    /// order, adjusted for `help_position` and `related_first`.
+    fn render_order(&self) -> Cow<'_, [Section]> {
+        if let Some(order) = &self.render_order {
+            return Cow::Borrowed(order);
+        }
+        let mut order = vec![Section::Header, Section::Message, Section::Causes];
+        if self.related_first {
+            order.push(Section::Related);
+        }
+        match self.help_position {
+            HelpPosition::BeforeSnippet => order.extend([Section::Help, Section::Snippets]),
+            HelpPosition::AfterSnippet => order.extend([Section::Snippets, Section::Help]),
+        }
+        if !self.related_first {
+            order.push(Section::Related);
+        }
+        order.push(Section::Footer);
+        Cow::Owned(order)
+    }
+
+    /// Renders one of the sections that look the same for top-level and
+    /// related diagnostics. The header, related diagnostics and footer are
+    /// left to the caller.
+    fn render_body_section(
+        &self,
+        f: &mut impl fmt::Write,
+        section: Section,
+        diagnostic: &dyn Diagnostic,
+        message: &str,
+        src: Option<&dyn SourceCode>,
+    ) -> fmt::Result {
+        match section {
+            Section::Message => self.render_message(f, diagnostic, message),
+            Section::Causes => self.render_cause_chain(f, diagnostic, src),
+            Section::Snippets => {
+                self.render_snippets(f, diagnostic, src)?;
+                self.render_context_snippets(f, diagnostic, src)
+            }
+            Section::Help => self.render_footer(f, diagnostic),
+            Section::Header | Section::Related | Section::Footer => Ok(()),
+        }
+    }
+
+    /// Renders the footer set with [`GraphicalReportHandler::with_footer`].
+    fn render_handler_footer(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        if let Some(footer) = self
+            .footer
+            .as_ref()
+            .filter(|_| self.sections.contains(RenderSections::FOOTER))
+        {
             writeln!(f)?;
             let width = self.termwidth.saturating_sub(2);
             let mut opts = textwrap::Options::new(width)
@@ -252,16 +1057,55 @@ impl GraphicalReportHandler {
         Ok(())
     }
 
-    fn render_header(&self, f: &mut impl fmt::Write, diagnostic: &(dyn Diagnostic)) -> fmt::Result {
-        let severity_style = match diagnostic.severity() {
-            Some(Severity::Error) | None => self.theme.styles.error,
-            Some(Severity::Warning) => self.theme.styles.warning,
-            Some(Severity::Advice) => self.theme.styles.advice,
+    /// Severity of `diagnostic`, falling back to the one derived from its code
+    /// (see [`GraphicalReportHandler::with_severity_from_code`]).
+    fn severity(&self, diagnostic: &dyn Diagnostic) -> Option<Severity> {
+        diagnostic.severity().or_else(|| {
+            let SeverityFromCode(mapper) = self.severity_from_code.as_ref()?;
+            mapper(&diagnostic.code()?.to_string())
+        })
+    }
+
+    /// Style for `severity`, set with
+    /// [`GraphicalReportHandler::with_severity_colors`] or else taken from
+    /// the theme.
+    fn severity_style(&self, severity: Option<Severity>) -> Style {
+        match severity {
+            Some(Severity::Error) | None => self.error_style.unwrap_or(self.theme.styles.error),
+            Some(Severity::Warning) => self.warning_style.unwrap_or(self.theme.styles.warning),
+            Some(Severity::Advice) => self.advice_style.unwrap_or(self.theme.styles.advice),
+        }
+    }
+
+    fn render_header(&self, f: &mut impl fmt::Write, diagnostic: &dyn Diagnostic) -> fmt::Result {
+        if !self.sections.contains(RenderSections::HEADER) {
+            return Ok(());
+        }
+        let severity = self.severity(diagnostic);
+        let severity_style = self.severity_style(severity);
+        let severity = match severity {
+            Some(Severity::Error) | None => "error",
+            Some(Severity::Warning) => "warning",
+            Some(Severity::Advice) => "advice",
         };
+        let code = diagnostic.code().map(|code| {
+            if self.severity_in_header {
+                format!("{}[{}]", severity, code)
+            } else {
+                code.to_string()
+            }
+        });
+        let related_count = diagnostic
+            .related()
+            .filter(|_| self.related_count_in_header)
+            .map(|related| related.count())
+            .filter(|count| *count > 0)
+            .map(|count| format!(" (+{} related)", count))
+            .unwrap_or_default();
         let mut header = String::new();
         if self.links == LinkStyle::Link && diagnostic.url().is_some() {
             let url = diagnostic.url().unwrap(); // safe
-            let code = if let Some(code) = diagnostic.code() {
+            let code = if let Some(code) = code {
                 format!("{} ", code)
             } else {
                 "".to_string()
@@ -273,35 +1117,56 @@ impl GraphicalReportHandler {
                 code.style(severity_style),
                 display_text.style(self.theme.styles.link)
             );
-            write!(header, "{}", link)?;
+            write!(header, "{}{}", link, related_count)?;
             writeln!(f, "{}", header)?;
-        } else if let Some(code) = diagnostic.code() {
+        } else if let Some(code) = code {
             write!(header, "{}", code.style(severity_style),)?;
             if self.links == LinkStyle::Text && diagnostic.url().is_some() {
                 let url = diagnostic.url().unwrap(); // safe
                 write!(header, " ({})", url.style(self.theme.styles.link))?;
             }
-            writeln!(f, "{}", header)?;
+            writeln!(f, "{}{}", header, related_count)?;
+        } else if self.always_show_header {
+            writeln!(f, "{}{}", severity.style(severity_style), related_count)?;
+        } else if !related_count.is_empty() {
+            writeln!(f, "{}", related_count.trim_start())?;
         }
         writeln!(f)?;
         Ok(())
     }
 
-    fn render_causes(
+    fn render_causes_with_message(
         &self,
         f: &mut impl fmt::Write,
-        diagnostic: &(dyn Diagnostic),
+        diagnostic: &dyn Diagnostic,
+        message: &str,
         parent_src: Option<&dyn SourceCode>,
     ) -> fmt::Result {
-        let src = diagnostic.source_code().or(parent_src);
+        self.render_message(f, diagnostic, message)?;
+        self.render_cause_chain(f, diagnostic, diagnostic.source_code().or(parent_src))
+    }
 
-        let (severity_style, severity_icon) = match diagnostic.severity() {
-            Some(Severity::Error) | None => (self.theme.styles.error, &self.theme.characters.error),
-            Some(Severity::Warning) => (self.theme.styles.warning, &self.theme.characters.warning),
-            Some(Severity::Advice) => (self.theme.styles.advice, &self.theme.characters.advice),
+    /// Renders the diagnostic's `×`-prefixed `message`, followed by its long
+    /// message, if any.
+    fn render_message(
+        &self,
+        f: &mut impl fmt::Write,
+        diagnostic: &dyn Diagnostic,
+        message: &str,
+    ) -> fmt::Result {
+        let severity = self.severity(diagnostic);
+        let severity_style = self.severity_style(severity);
+        let (severity_icon, severity_word) = match severity {
+            Some(Severity::Error) | None => (&self.theme.characters.error, "error"),
+            Some(Severity::Warning) => (&self.theme.characters.warning, "warning"),
+            Some(Severity::Advice) => (&self.theme.characters.advice, "advice"),
         };
 
-        let initial_indent = format!("  {} ", severity_icon.style(severity_style));
+        let initial_indent = if self.text_severity {
+            format!("  {} ", format!("{}:", severity_word).style(severity_style))
+        } else {
+            format!("  {} ", severity_icon.style(severity_style))
+        };
         let rest_indent = format!("  {} ", self.theme.characters.vbar.style(severity_style));
         let width = self.termwidth.saturating_sub(2);
         let mut opts = textwrap::Options::new(width)
@@ -315,9 +1180,40 @@ impl GraphicalReportHandler {
             opts = opts.word_splitter(word_splitter);
         }
 
-        writeln!(f, "{}", self.wrap(&diagnostic.to_string(), opts))?;
+        if self.bold_message {
+            let message = message.style(severity_style.bold()).to_string();
+            writeln!(f, "{}", self.wrap(&message, opts))?;
+        } else {
+            writeln!(f, "{}", self.wrap(message, opts))?;
+        }
+
+        if let Some(long_message) = diagnostic.long_message().filter(|_| !self.compact) {
+            let mut opts = textwrap::Options::new(width)
+                .initial_indent("    ")
+                .subsequent_indent("    ")
+                .break_words(self.break_words);
+            if let Some(word_separator) = self.word_separator {
+                opts = opts.word_separator(word_separator);
+            }
+            if let Some(word_splitter) = self.word_splitter.clone() {
+                opts = opts.word_splitter(word_splitter);
+            }
+            writeln!(f, "{}", self.wrap(&long_message.to_string(), opts))?;
+        }
+        Ok(())
+    }
+
+    /// Renders the chain of `diagnostic`'s causes, below its message.
+    fn render_cause_chain(
+        &self,
+        f: &mut impl fmt::Write,
+        diagnostic: &dyn Diagnostic,
+        src: Option<&dyn SourceCode>,
+    ) -> fmt::Result {
+        let severity_style = self.severity_style(self.severity(diagnostic));
+        let width = self.termwidth.saturating_sub(2);
 
-        if !self.with_cause_chain {
+        if !self.with_cause_chain || !self.sections.contains(RenderSections::CAUSES) {
             return Ok(());
         }
 
@@ -361,6 +1257,12 @@ impl GraphicalReportHandler {
                     opts = opts.word_splitter(word_splitter);
                 }
 
+                let code = if self.render_cause_codes {
+                    error.as_diagnostic().and_then(|diag| diag.code())
+                } else {
+                    None
+                };
+
                 match error {
                     ErrorKind::Diagnostic(diag) => {
                         let mut inner = String::new();
@@ -371,89 +1273,346 @@ impl GraphicalReportHandler {
                         // Cause chains are already flattened, so don't double-print the nested error
                         inner_renderer.with_cause_chain = false;
                         // Since everything from here on is indented, shrink the virtual terminal
-                        inner_renderer.termwidth -= rest_indent.width();
-                        inner_renderer.render_report_inner(&mut inner, diag, src)?;
+                        inner_renderer.termwidth -= textwrap::core::display_width(&rest_indent);
+                        // The code goes on the message line instead of in a header.
+                        let message = code.map(|code| format!("[{}] {}", code, diag));
+                        inner_renderer.render_report_inner(
+                            &mut inner,
+                            diag,
+                            message.as_deref(),
+                            src,
+                        )?;
+
+                        // If there was no header, remove the leading newline
+                        let inner = inner.trim_start_matches('\n');
+                        writeln!(f, "{}", self.wrap(inner, opts))?;
+                    }
+                    ErrorKind::StdError(err) => {
+                        let message = match code {
+                            Some(code) => format!("[{}] {}", code, err),
+                            None => err.to_string(),
+                        };
+                        writeln!(f, "{}", self.wrap(&message, opts))?;
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn render_footer(&self, f: &mut impl fmt::Write, diagnostic: &dyn Diagnostic) -> fmt::Result {
+        if !self.sections.contains(RenderSections::HELP) {
+            return Ok(());
+        }
+        let help = diagnostic.help().map(|help| help.to_string()).or_else(|| {
+            let severity = self.severity(diagnostic).unwrap_or_default();
+            self.default_help.get(&severity).cloned()
+        });
+        if let Some(help) = help {
+            let width = self.termwidth.saturating_sub(2);
+            let initial_indent = "  help: ".style(self.theme.styles.help).to_string();
+            let mut opts = textwrap::Options::new(width)
+                .initial_indent(&initial_indent)
+                .subsequent_indent("        ")
+                .break_words(self.break_words);
+            if let Some(word_separator) = self.word_separator {
+                opts = opts.word_separator(word_separator);
+            }
+            if let Some(word_splitter) = self.word_splitter.clone() {
+                opts = opts.word_splitter(word_splitter);
+            }
+
+            writeln!(f, "{}", self.wrap(&help, opts))?;
+        }
+        Ok(())
+    }
+
+    /// Styles for each of `labels`: primary labels get the style set with
+    /// [`GraphicalReportHandler::with_primary_style`], if any, while all
+    /// others cycle through the theme's highlight styles.
+    fn label_styles<'a>(&'a self, labels: &'a [LabeledSpan]) -> impl Iterator<Item = Style> + 'a {
+        let mut highlights = self.theme.styles.highlights.iter().cloned().cycle();
+        labels.iter().map(move |label| match self.primary_style {
+            Some(style) if label.primary() => style,
+            _ => highlights.next().unwrap_or_default(),
+        })
+    }
+
+    /// Display form of a source name, relative to
+    /// [`GraphicalReportHandler::with_path_base`] if possible.
+    fn source_name<'a>(&self, name: &'a str) -> Cow<'a, str> {
+        match &self.path_base {
+            Some(base) => match Path::new(name).strip_prefix(base) {
+                Ok(relative) => Cow::Owned(relative.display().to_string()),
+                Err(_) => Cow::Borrowed(name),
+            },
+            None => Cow::Borrowed(name),
+        }
+    }
+
+    fn render_related(
+        &self,
+        f: &mut impl fmt::Write,
+        diagnostic: &dyn Diagnostic,
+        parent_src: Option<&dyn SourceCode>,
+    ) -> fmt::Result {
+        if !self.sections.contains(RenderSections::RELATED) {
+            return Ok(());
+        }
+        if let Some(related) = diagnostic.related() {
+            if self.group_related_by_file {
+                let inner_renderer = self.related_renderer();
+                // Buckets are kept in order of first appearance.
+                let mut groups: Vec<(Option<String>, Vec<&dyn Diagnostic>)> = Vec::new();
+                for rel in related {
+                    let name = related_source_name(rel, parent_src);
+                    match groups.iter_mut().find(|(group, _)| *group == name) {
+                        Some((_, rels)) => rels.push(rel),
+                        None => groups.push((name, vec![rel])),
+                    }
+                }
+                for (i, (name, rels)) in groups.into_iter().enumerate() {
+                    // The separator goes before a group's header, if any.
+                    if i > 0 {
+                        self.render_related_separator(f)?;
+                    }
+                    if let Some(name) = name {
+                        writeln!(f)?;
+                        writeln!(
+                            f,
+                            "{}",
+                            format!("[{}]", self.source_name(&name)).style(self.theme.styles.link)
+                        )?;
+                    }
+                    for (j, rel) in rels.into_iter().enumerate() {
+                        if j > 0 {
+                            self.render_related_separator(f)?;
+                        }
+                        inner_renderer.render_related_single(f, rel, parent_src)?;
+                    }
+                }
+            } else {
+                self.render_related_streaming(f, related, parent_src)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Render a sequence of related [`Diagnostic`]s the same way they would
+    /// be rendered by [`Diagnostic::related()`]. Each diagnostic is rendered
+    /// as soon as it's produced by `related`, so the iterator is never
+    /// collected, which keeps memory usage flat for very large sets of
+    /// related diagnostics.
+    ///
+    /// `parent_src` is used for any related diagnostic that doesn't provide
+    /// its own [`Diagnostic::source_code()`].
+    pub fn render_related_streaming<'a>(
+        &self,
+        f: &mut impl fmt::Write,
+        related: impl Iterator<Item = &'a dyn Diagnostic>,
+        parent_src: Option<&dyn SourceCode>,
+    ) -> fmt::Result {
+        let inner_renderer = self.related_renderer();
+        for (i, rel) in related.enumerate() {
+            if i > 0 {
+                self.render_related_separator(f)?;
+            }
+            inner_renderer.render_related_single(f, rel, parent_src)?;
+        }
+        Ok(())
+    }
+
+    fn render_related_separator(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        match &self.related_separator {
+            Some(separator) => writeln!(f, "{}", separator),
+            None => Ok(()),
+        }
+    }
+
+    fn related_renderer(&self) -> Self {
+        let mut inner_renderer = self.clone();
+        // Re-enable the printing of nested cause chains for related errors
+        inner_renderer.with_cause_chain = true;
+        inner_renderer
+    }
+
+    fn render_related_single(
+        &self,
+        f: &mut impl fmt::Write,
+        rel: &dyn Diagnostic,
+        parent_src: Option<&dyn SourceCode>,
+    ) -> fmt::Result {
+        if self.render_related_header {
+            for _ in 0..self.related_spacing {
+                writeln!(f)?;
+            }
+        }
+        let owned_src = owned_source_code(rel);
+        let src = rel.source_code().or(owned_src.as_deref()).or(parent_src);
+        let message = rel.to_string();
+        for section in self.render_order().iter() {
+            match section {
+                Section::Header => self.render_related_header(f, rel)?,
+                Section::Related => self.render_nested_related(f, rel, src)?,
+                // The handler's footer only goes after the whole report.
+                Section::Footer => {}
+                _ => self.render_body_section(f, *section, rel, &message, src)?,
+            }
+        }
+        Ok(())
+    }
+
+    fn render_related_header(&self, f: &mut impl fmt::Write, rel: &dyn Diagnostic) -> fmt::Result {
+        if self.render_related_header {
+            if self.sections.contains(RenderSections::HEADER) {
+                match self.severity(rel) {
+                    Some(Severity::Error) | None => write!(f, "Error: ")?,
+                    Some(Severity::Warning) => write!(f, "Warning: ")?,
+                    Some(Severity::Advice) => write!(f, "Advice: ")?,
+                };
+            }
+            return self.render_header(f, rel);
+        }
+        // Without the related header, an otherwise empty header would only
+        // leave a blank line between the diagnostics.
+        let mut header = String::new();
+        self.render_header(&mut header, rel)?;
+        if header != "\n" {
+            f.write_str(&header)?;
+        }
+        Ok(())
+    }
+
+    fn render_nested_related(
+        &self,
+        f: &mut impl fmt::Write,
+        rel: &dyn Diagnostic,
+        src: Option<&dyn SourceCode>,
+    ) -> fmt::Result {
+        if self.related_indent == 0 {
+            return self.render_related(f, rel, src);
+        }
+
+        // Nested related diagnostics are one level deeper, so render them
+        // into a buffer and shift them over.
+        let mut inner_renderer = self.clone();
+        inner_renderer.termwidth = self.termwidth.saturating_sub(self.related_indent);
+        let mut nested = String::new();
+        inner_renderer.render_related(&mut nested, rel, src)?;
+        let indent = " ".repeat(self.related_indent);
+        for line in nested.split_inclusive('\n') {
+            if line != "\n" {
+                f.write_str(&indent)?;
+            }
+            f.write_str(line)?;
+        }
+        Ok(())
+    }
+
+    fn render_snippets(
+        &self,
+        f: &mut impl fmt::Write,
+        diagnostic: &dyn Diagnostic,
+        opt_source: Option<&dyn SourceCode>,
+    ) -> fmt::Result {
+        if !self.sections.contains(RenderSections::SNIPPETS) {
+            return Ok(());
+        }
+        let labels = match diagnostic.labels() {
+            Some(labels) => labels,
+            None => return Ok(()),
+        };
+        let source = match opt_source {
+            Some(source) => source,
+            None if self.show_missing_source => return self.render_missing_source(f, labels),
+            None => return Ok(()),
+        };
 
-                        // If there was no header, remove the leading newline
-                        let inner = inner.trim_start_matches('\n');
-                        writeln!(f, "{}", self.wrap(inner, opts))?;
-                    }
-                    ErrorKind::StdError(err) => {
-                        writeln!(f, "{}", self.wrap(&err.to_string(), opts))?;
-                    }
+        let mut labels = labels.collect::<Vec<_>>();
+        if self.numbered_labels {
+            for (i, label) in labels.iter_mut().enumerate() {
+                if let Some(text) = label.label() {
+                    let text = format!("{} {}", LabelRef(i + 1), text);
+                    label.set_label(Some(text));
                 }
             }
         }
+        labels.sort_unstable_by_key(|l| l.inner().offset());
 
-        Ok(())
-    }
+        if let Some(diff) = source.as_diff().filter(|_| self.diff_view) {
+            self.render_diff(f, diff, &labels)?;
+            return self.render_label_legend(f, &labels);
+        }
 
-    fn render_footer(&self, f: &mut impl fmt::Write, diagnostic: &(dyn Diagnostic)) -> fmt::Result {
-        if let Some(help) = diagnostic.help() {
-            let width = self.termwidth.saturating_sub(2);
-            let initial_indent = "  help: ".style(self.theme.styles.help).to_string();
-            let mut opts = textwrap::Options::new(width)
-                .initial_indent(&initial_indent)
-                .subsequent_indent("        ")
-                .break_words(self.break_words);
-            if let Some(word_separator) = self.word_separator {
-                opts = opts.word_separator(word_separator);
+        if self.classic {
+            for (label, style) in labels.iter().zip(self.label_styles(&labels)) {
+                self.render_classic_label(f, diagnostic, source, label, style)?;
             }
-            if let Some(word_splitter) = self.word_splitter.clone() {
-                opts = opts.word_splitter(word_splitter);
-            }
-
-            writeln!(f, "{}", self.wrap(&help.to_string(), opts))?;
+            return self.render_label_legend(f, &labels);
         }
-        Ok(())
+
+        self.render_contexts(f, source, &labels)?;
+        self.render_label_legend(f, &labels)
     }
 
-    fn render_related(
+    fn render_missing_source(
         &self,
         f: &mut impl fmt::Write,
-        diagnostic: &(dyn Diagnostic),
-        parent_src: Option<&dyn SourceCode>,
+        labels: impl Iterator<Item = LabeledSpan>,
     ) -> fmt::Result {
-        if let Some(related) = diagnostic.related() {
-            let mut inner_renderer = self.clone();
-            // Re-enable the printing of nested cause chains for related errors
-            inner_renderer.with_cause_chain = true;
-            for rel in related {
-                writeln!(f)?;
-                match rel.severity() {
-                    Some(Severity::Error) | None => write!(f, "Error: ")?,
-                    Some(Severity::Warning) => write!(f, "Warning: ")?,
-                    Some(Severity::Advice) => write!(f, "Advice: ")?,
-                };
-                inner_renderer.render_header(f, rel)?;
-                let src = rel.source_code().or(parent_src);
-                inner_renderer.render_causes(f, rel, src)?;
-                inner_renderer.render_snippets(f, rel, src)?;
-                inner_renderer.render_footer(f, rel)?;
-                inner_renderer.render_related(f, rel, src)?;
-            }
+        for label in labels {
+            let label_text = label
+                .label()
+                .map(|text| format!(" '{}'", text))
+                .unwrap_or_default();
+            let note = format!(
+                "[no source available for label{} at {}..{}]",
+                label_text,
+                label.offset(),
+                label.offset() + label.len()
+            );
+            writeln!(f, "  {}", note.style(self.theme.styles.warning))?;
         }
         Ok(())
     }
 
-    fn render_snippets(
+    /// Renders the diagnostic's [`Diagnostic::context_snippets`], each below
+    /// its caption and without any underlines.
+    fn render_context_snippets(
         &self,
         f: &mut impl fmt::Write,
-        diagnostic: &(dyn Diagnostic),
+        diagnostic: &dyn Diagnostic,
         opt_source: Option<&dyn SourceCode>,
     ) -> fmt::Result {
+        if !self.sections.contains(RenderSections::SNIPPETS) {
+            return Ok(());
+        }
         let source = match opt_source {
             Some(source) => source,
             None => return Ok(()),
         };
-        let labels = match diagnostic.labels() {
-            Some(labels) => labels,
+        let snippets = match diagnostic.context_snippets() {
+            Some(snippets) => snippets,
             None => return Ok(()),
         };
+        for snippet in snippets {
+            if let Some(caption) = snippet.label() {
+                writeln!(f, "  {}", caption)?;
+            }
+            self.render_context(f, source, &snippet, &[])?;
+        }
+        Ok(())
+    }
 
-        let mut labels = labels.collect::<Vec<_>>();
-        labels.sort_unstable_by_key(|l| l.inner().offset());
-
+    /// Renders `labels`, sorted by offset, as snippets of `source`, merging
+    /// the contexts of labels that are close together.
+    fn render_contexts(
+        &self,
+        f: &mut impl fmt::Write,
+        source: &dyn SourceCode,
+        labels: &[LabeledSpan],
+    ) -> fmt::Result {
         let mut contexts = Vec::with_capacity(labels.len());
         for right in labels.iter().cloned() {
             let right_conts =
@@ -482,8 +1641,11 @@ impl GraphicalReportHandler {
             }
 
             let (left, left_conts) = contexts.last().unwrap();
-            if left_conts.line() + left_conts.line_count() >= right_conts.line() {
-                // The snippets will overlap, so we create one Big Chunky Boi
+            if left_conts.line() + left_conts.line_count() + self.context_merge_gap
+                >= right_conts.line()
+            {
+                // The snippets will overlap (or are close enough), so we
+                // create one Big Chunky Boi
                 let left_end = left.offset() + left.len();
                 let right_end = right.offset() + right.len();
                 let new_end = std::cmp::max(left_end, right_end);
@@ -507,9 +1669,153 @@ impl GraphicalReportHandler {
             contexts.push((right, right_conts));
         }
         for (ctx, _) in contexts {
-            self.render_context(f, source, &ctx, &labels[..])?;
+            self.render_context(f, source, &ctx, labels)?;
+        }
+        Ok(())
+    }
+
+    /// Renders the two versions of `diff` side by side, each with the
+    /// labels that point into it.
+    fn render_diff(
+        &self,
+        f: &mut impl fmt::Write,
+        diff: &DiffSource,
+        labels: &[LabeledSpan],
+    ) -> fmt::Result {
+        let with_span = |label: &LabeledSpan, span: SourceSpan| {
+            let text = label.label().map(String::from);
+            let moved = if label.primary() {
+                LabeledSpan::new_primary_with_span(text, span)
+            } else {
+                LabeledSpan::new_with_span(text, span)
+            };
+            moved.with_label_style(label.label_style())
+        };
+        let mut before_labels = Vec::new();
+        let mut after_labels = Vec::new();
+        for label in labels {
+            let (before_part, after_part) = if label.offset() >= diff.split() {
+                (None, Some(*label.inner()))
+            } else if label.offset() + label.len() <= diff.split() {
+                (Some(*label.inner()), None)
+            } else {
+                // The label crosses from one version into the other, so each
+                // side gets the part that points into it.
+                let (head, tail) = label.inner().split_at(diff.split()).unwrap();
+                (Some(head), Some(tail))
+            };
+            if let Some(span) = before_part {
+                before_labels.push(with_span(label, span));
+            }
+            if let Some(span) = after_part {
+                let span = (span.offset() - diff.split(), span.len()).into();
+                after_labels.push(with_span(label, span));
+            }
+        }
+
+        let mut before = String::new();
+        self.render_contexts(&mut before, &diff.before(), &before_labels)?;
+        let mut after = String::new();
+        self.render_contexts(&mut after, &diff.after(), &after_labels)?;
+
+        let before = before.lines().collect::<Vec<_>>();
+        let after = after.lines().collect::<Vec<_>>();
+        let width = before
+            .iter()
+            .map(|line| textwrap::core::display_width(line))
+            .max()
+            .unwrap_or(0);
+        for i in 0..std::cmp::max(before.len(), after.len()) {
+            let left = before.get(i).copied().unwrap_or_default();
+            let right = after.get(i).copied().unwrap_or_default();
+            let padding = width - textwrap::core::display_width(left);
+            let row = format!("{}{}  {}", left, " ".repeat(padding), right);
+            writeln!(f, "{}", row.trim_end())?;
         }
+        Ok(())
+    }
+
+    fn render_label_legend(&self, f: &mut impl fmt::Write, labels: &[LabeledSpan]) -> fmt::Result {
+        if !self.label_legend {
+            return Ok(());
+        }
+        let swatch = self.theme.characters.hbar.to_string().repeat(3);
+        for (label, style) in labels.iter().zip(self.label_styles(labels)) {
+            if let Some(text) = label.label() {
+                writeln!(
+                    f,
+                    "  {} {}",
+                    swatch.style(style),
+                    text.replace('\n', " ").style(style)
+                )?;
+            }
+        }
+        Ok(())
+    }
 
+    fn render_classic_label(
+        &self,
+        f: &mut impl fmt::Write,
+        diagnostic: &dyn Diagnostic,
+        source: &dyn SourceCode,
+        label: &LabeledSpan,
+        style: Style,
+    ) -> fmt::Result {
+        let contents = source
+            .read_span(label.inner(), 0, 0)
+            .map_err(|_| fmt::Error)?;
+        let location = match contents.name() {
+            Some(name) => format!(
+                "{}:{}:{}",
+                self.source_name(name),
+                contents.line() + 1,
+                contents.column() + 1
+            ),
+            None => format!("{}:{}", contents.line() + 1, contents.column() + 1),
+        };
+        let message = label
+            .label()
+            .map(String::from)
+            .unwrap_or_else(|| diagnostic.to_string());
+        writeln!(f, "{}: {}", location.style(self.theme.styles.link), message)?;
+
+        // Only the line the label starts on is shown, classic-style. Reading
+        // without any context would start the data at the label itself, so
+        // read one line around it and pick out the starting line.
+        let (line_contents, lines) = self.get_lines_with_context(source, label.inner(), 1)?;
+        let line = match lines
+            .iter()
+            .rev()
+            .find(|line| line.offset <= label.offset())
+        {
+            Some(line) => line,
+            None => return Ok(()),
+        };
+        let mut highlighter_state = self.highlighter.start_highlighter_state(&*line_contents);
+        let deletions = match label.label_style() {
+            LabelStyle::Deletion => vec![*label.inner()],
+            LabelStyle::Normal => vec![],
+        };
+        let styled_text = self.style_segments(
+            line,
+            highlighter_state.highlight_line(&line.text),
+            &deletions,
+        );
+        self.render_line_text(f, line, &styled_text, &[*label.inner()])?;
+
+        let start = self.visual_offset(line, label.offset(), true);
+        let end = if label.offset() + label.len() <= line.offset + line.length {
+            self.visual_offset(line, label.offset() + label.len(), false)
+        } else {
+            self.line_visual_char_width(&line.text).sum()
+        };
+        writeln!(
+            f,
+            "{:width$}{}",
+            "",
+            "^".repeat(end.saturating_sub(start).max(1)).style(style),
+            width = start
+        )?;
         Ok(())
     }
 
@@ -522,6 +1828,20 @@ impl GraphicalReportHandler {
     ) -> fmt::Result {
         let (contents, lines) = self.get_lines(source, context.inner())?;
 
+        if let Some(tab_width) = contents.tab_width().filter(|_| self.tab_width.is_none()) {
+            let mut renderer = self.clone();
+            renderer.tab_width = Some(tab_width);
+            return renderer.render_context(f, source, context, labels);
+        }
+
+        if self.collapse_empty_source && contents.data().is_empty() {
+            let note = match contents.name() {
+                Some(name) => format!("(empty source: {})", self.source_name(name)),
+                None => "(empty source)".to_string(),
+            };
+            return writeln!(f, "  {}", note.style(self.theme.styles.link));
+        }
+
         // only consider labels from the context as primary label
         let ctx_labels = labels.iter().filter(|l| {
             context.inner().offset() <= l.inner().offset()
@@ -533,11 +1853,29 @@ impl GraphicalReportHandler {
             .find(|label| label.primary())
             .or_else(|| ctx_labels.clone().next());
 
+        let deletions = labels
+            .iter()
+            .filter(|label| label.label_style() == LabelStyle::Deletion)
+            .map(|label| *label.inner())
+            .collect::<Vec<_>>();
+
+        let label_spans = labels
+            .iter()
+            .map(|label| *label.inner())
+            .collect::<Vec<_>>();
+
         // sorting is your friend
         let labels = labels
             .iter()
-            .zip(self.theme.styles.highlights.iter().cloned().cycle())
-            .map(|(label, st)| FancySpan::new(label.label().map(String::from), *label.inner(), st))
+            .zip(self.label_styles(labels))
+            .map(|(label, st)| {
+                FancySpan::new(
+                    label.label().map(String::from),
+                    *label.inner(),
+                    st,
+                    label.primary(),
+                )
+            })
             .collect::<Vec<_>>();
 
         let mut highlighter_state = self.highlighter.start_highlighter_state(&*contents);
@@ -558,13 +1896,30 @@ impl GraphicalReportHandler {
 
         // Oh and one more thing: We need to figure out how much room our line
         // numbers need!
-        let linum_width = lines[..]
-            .last()
-            .map(|line| line.line_number)
+        let linums = lines
+            .iter()
+            .map(|line| {
+                let column = labels
+                    .iter()
+                    .map(|label| label.offset())
+                    .filter(|offset| {
+                        self.show_columns
+                            && line.offset <= *offset
+                            && *offset < line.offset + line.length
+                    })
+                    .min();
+                match column {
+                    Some(offset) => format!("{}:{}", line.line_number, offset - line.offset + 1),
+                    None => line.line_number.to_string(),
+                }
+            })
+            .collect::<Vec<_>>();
+        let linum_width = linums
+            .iter()
+            .map(|linum| linum.len())
+            .max()
             // It's possible for the source to be an empty string.
-            .unwrap_or(0)
-            .to_string()
-            .len();
+            .unwrap_or(1);
 
         // Header
         write!(
@@ -584,33 +1939,45 @@ impl GraphicalReportHandler {
             None => contents,
         };
 
+        let caption = primary_label
+            .and_then(|label| label.label())
+            .filter(|_| self.snippet_caption)
+            .map(|text| format!(" {}", text))
+            .unwrap_or_default();
         if let Some(source_name) = primary_contents.name() {
             writeln!(
                 f,
-                "[{}]",
+                "[{}]{}",
                 format_args!(
                     "{}:{}:{}",
-                    source_name,
+                    self.source_name(source_name),
                     primary_contents.line() + 1,
                     primary_contents.column() + 1
                 )
-                .style(self.theme.styles.link)
+                .style(self.theme.styles.link),
+                caption
             )?;
         } else if lines.len() <= 1 {
-            writeln!(f, "{}", self.theme.characters.hbar.to_string().repeat(3))?;
+            writeln!(
+                f,
+                "{}{}",
+                self.theme.characters.hbar.to_string().repeat(3),
+                caption
+            )?;
         } else {
             writeln!(
                 f,
-                "[{}:{}]",
+                "[{}:{}]{}",
                 primary_contents.line() + 1,
-                primary_contents.column() + 1
+                primary_contents.column() + 1,
+                caption
             )?;
         }
 
         // Now it's time for the fun part--actually rendering everything!
-        for line in &lines {
+        for (line, linum) in lines.iter().zip(&linums) {
             // Line number, appropriately padded.
-            self.write_linum(f, linum_width, line.line_number)?;
+            self.write_linum(f, linum_width, linum)?;
 
             // Then, we need to print the gutter, along with any fly-bys We
             // have separate gutters depending on whether we're on the actual
@@ -618,9 +1985,12 @@ impl GraphicalReportHandler {
             self.render_line_gutter(f, max_gutter, line, &labels)?;
 
             // And _now_ we can print out the line text itself!
-            let styled_text =
-                StyledList::from(highlighter_state.highlight_line(&line.text)).to_string();
-            self.render_line_text(f, &styled_text)?;
+            let styled_text = self.style_segments(
+                line,
+                highlighter_state.highlight_line(&line.text),
+                &deletions,
+            );
+            self.render_line_text(f, line, &styled_text, &label_spans)?;
 
             // Next, we write all the highlights that apply to this particular line.
             let (single_line, multi_line): (Vec<_>, Vec<_>) = labels
@@ -675,6 +2045,15 @@ impl GraphicalReportHandler {
         // no line number!
         self.write_no_linum(f, linum_width)?;
 
+        // If the span ends before the end of the line's text, the connector
+        // is drawn out to the column it ends at.
+        let span_end = label.offset() + label.len();
+        let end_width = if line.offset < span_end && span_end < line.offset + line.text.len() {
+            Some(self.visual_offset(line, span_end, false))
+        } else {
+            None
+        };
+
         if let Some(label_parts) = label.label_parts() {
             // if it has a label, how long is it?
             let (first, rest) = label_parts
@@ -690,7 +2069,12 @@ impl GraphicalReportHandler {
                     labels,
                     LabelRenderMode::SingleLine,
                 )?;
-
+                self.render_multi_line_end_column(
+                    f,
+                    end_width,
+                    label.style,
+                    LabelRenderMode::SingleLine,
+                )?;
                 self.render_multi_line_end_single(
                     f,
                     first,
@@ -706,7 +2090,12 @@ impl GraphicalReportHandler {
                     labels,
                     LabelRenderMode::MultiLineFirst,
                 )?;
-
+                self.render_multi_line_end_column(
+                    f,
+                    end_width,
+                    label.style,
+                    LabelRenderMode::MultiLineFirst,
+                )?;
                 self.render_multi_line_end_single(
                     f,
                     first,
@@ -724,6 +2113,12 @@ impl GraphicalReportHandler {
                         labels,
                         LabelRenderMode::MultiLineRest,
                     )?;
+                    self.render_multi_line_end_column(
+                        f,
+                        end_width,
+                        label.style,
+                        LabelRenderMode::MultiLineRest,
+                    )?;
                     self.render_multi_line_end_single(
                         f,
                         label_line,
@@ -923,14 +2318,18 @@ impl GraphicalReportHandler {
         }
     }
 
-    fn write_linum(&self, f: &mut impl fmt::Write, width: usize, linum: usize) -> fmt::Result {
-        write!(
-            f,
-            " {:width$} {} ",
-            linum.style(self.theme.styles.linum),
-            self.theme.characters.vbar,
-            width = width
-        )?;
+    fn write_linum(
+        &self,
+        f: &mut impl fmt::Write,
+        width: usize,
+        linum: impl fmt::Display,
+    ) -> fmt::Result {
+        let separator = self.gutter_separator.unwrap_or(self.theme.characters.vbar);
+        let linum = linum.style(self.theme.styles.linum);
+        match self.gutter_align {
+            GutterAlign::Left => write!(f, " {:<width$} {} ", linum, separator, width = width)?,
+            GutterAlign::Right => write!(f, " {:>width$} {} ", linum, separator, width = width)?,
+        }
         Ok(())
     }
 
@@ -949,7 +2348,7 @@ impl GraphicalReportHandler {
     fn line_visual_char_width<'a>(&self, text: &'a str) -> impl Iterator<Item = usize> + 'a {
         let mut column = 0;
         let mut escaped = false;
-        let tab_width = self.tab_width;
+        let tab_width = self.tab_width.unwrap_or(4);
         text.chars().map(move |c| {
             let width = match (escaped, c) {
                 // Round up to the next multiple of tab_width
@@ -1008,15 +2407,151 @@ impl GraphicalReportHandler {
         }
     }
 
+    /// Whether the theme leaves everything unstyled, in which case styles
+    /// the theme doesn't cover aren't added by default either.
+    fn plain_theme(&self) -> bool {
+        let styles = &self.theme.styles;
+        [
+            styles.error,
+            styles.warning,
+            styles.advice,
+            styles.help,
+            styles.link,
+            styles.linum,
+        ]
+        .iter()
+        .chain(&styles.highlights)
+        .all(|style| *style == Style::new())
+    }
+
+    /// Style for source text covered by deletion labels, set with
+    /// [`GraphicalReportHandler::with_deletion_style`].
+    fn deletion_style(&self) -> Style {
+        match self.deletion_style {
+            Some(style) => style,
+            None if self.plain_theme() => Style::new(),
+            None => Style::new().strikethrough(),
+        }
+    }
+
+    /// Style for source text outside of the focus, set with
+    /// [`GraphicalReportHandler::with_dimmed_style`].
+    fn dimmed_style(&self) -> Style {
+        match self.dimmed_style {
+            Some(style) => style,
+            None if self.plain_theme() => Style::new(),
+            None => Style::new().dimmed(),
+        }
+    }
+
+    /// Joins the highlighted segments of `line`, striking through whatever
+    /// the `deletions` cover and dimming whatever falls outside of the focus
+    /// span, if there is one.
+    fn style_segments(
+        &self,
+        line: &Line,
+        segments: Vec<Styled<&str>>,
+        deletions: &[SourceSpan],
+    ) -> String {
+        if deletions.is_empty() && self.focus.is_none() {
+            return StyledList::from(segments).to_string();
+        }
+        let covers = |span: &SourceSpan, offset: usize| {
+            span.offset() <= offset && offset < span.offset() + span.len()
+        };
+        let is_deleted = |offset: usize| deletions.iter().any(|span| covers(span, offset));
+        let is_dimmed = |offset: usize| self.focus.is_some_and(|focus| !covers(&focus, offset));
+        let mut out = String::new();
+        let mut offset = line.offset;
+        for segment in segments {
+            let text = *segment.inner();
+            // Split the segment wherever a deletion or the focus starts or
+            // ends.
+            let mut bounds = deletions
+                .iter()
+                .chain(&self.focus)
+                .flat_map(|span| {
+                    std::iter::once(span.offset()).chain(Some(span.offset() + span.len()))
+                })
+                .filter(|&bound| bound > offset && bound < offset + text.len())
+                .map(|bound| bound - offset)
+                .filter(|&bound| text.is_char_boundary(bound))
+                .collect::<Vec<_>>();
+            bounds.sort_unstable();
+            bounds.dedup();
+            bounds.push(text.len());
+            let mut start = 0;
+            for end in bounds {
+                let piece = segment.style.style(&text[start..end]);
+                let deleted = is_deleted(offset + start);
+                let dimmed = is_dimmed(offset + start);
+                match (deleted, dimmed) {
+                    (true, true) => write!(
+                        out,
+                        "{}",
+                        piece
+                            .style(self.deletion_style())
+                            .style(self.dimmed_style())
+                    ),
+                    (true, false) => write!(out, "{}", piece.style(self.deletion_style())),
+                    (false, true) => write!(out, "{}", piece.style(self.dimmed_style())),
+                    (false, false) => write!(out, "{}", piece),
+                }
+                .expect("writing to a String can't fail");
+                start = end;
+            }
+            offset += text.len();
+        }
+        out
+    }
+
     /// Renders a line to the output formatter, replacing tabs with spaces.
-    fn render_line_text(&self, f: &mut impl fmt::Write, text: &str) -> fmt::Result {
+    /// If `show_whitespace` is set, whitespace within `spans` is rendered
+    /// visibly instead.
+    fn render_line_text(
+        &self,
+        f: &mut impl fmt::Write,
+        line: &Line,
+        text: &str,
+        spans: &[SourceSpan],
+    ) -> fmt::Result {
+        let visible = |offset: usize| {
+            self.show_whitespace
+                && spans
+                    .iter()
+                    .any(|span| span.offset() <= offset && offset < span.offset() + span.len())
+        };
+        // Offset into the source, skipping over any styling escapes.
+        let mut offset = line.offset;
+        let mut escaped = false;
         for (c, width) in text.chars().zip(self.line_visual_char_width(text)) {
-            if c == '\t' {
-                for _ in 0..width {
-                    f.write_char(' ')?;
+            let is_source = !escaped && c != '\x1b';
+            match (escaped, c) {
+                (false, '\x1b') => {
+                    escaped = true;
+                    f.write_char(c)?;
                 }
-            } else {
-                f.write_char(c)?;
+                (true, _) => {
+                    escaped = c != 'm';
+                    f.write_char(c)?;
+                }
+                (false, ' ') if visible(offset) => {
+                    write!(f, "{}", '·'.style(self.theme.styles.linum))?;
+                }
+                (false, '\t') => {
+                    let mut width = width;
+                    if visible(offset) {
+                        write!(f, "{}", '→'.style(self.theme.styles.linum))?;
+                        width -= 1;
+                    }
+                    for _ in 0..width {
+                        f.write_char(' ')?;
+                    }
+                }
+                (false, _) => f.write_char(c)?,
+            }
+            if is_source {
+                offset += c.len_utf8();
             }
         }
         f.write_char('\n')?;
@@ -1035,6 +2570,17 @@ impl GraphicalReportHandler {
         let mut underlines = String::new();
         let mut highest = 0;
 
+        // Too many labels get numbered underlines and a list of their texts,
+        // rather than a connector row each.
+        let labeled = single_liners
+            .iter()
+            .filter(|hl| hl.label().is_some())
+            .count();
+        let compact = self
+            .inline_label_threshold
+            .is_some_and(|threshold| labeled > threshold);
+        let mut label_number = 0;
+
         let chars = &self.theme.characters;
         let vbar_offsets: Vec<_> = single_liners
             .iter()
@@ -1049,33 +2595,68 @@ impl GraphicalReportHandler {
                 };
 
                 let vbar_offset = (start + end) / 2;
-                let num_left = vbar_offset - start;
-                let num_right = end - vbar_offset - 1;
+                let underline = if hl.primary {
+                    chars.underline
+                } else {
+                    self.secondary_underline.unwrap_or(chars.underline)
+                };
+                let marker = if hl.len() == 0 {
+                    self.empty_span_marker.unwrap_or(chars.uarrow).to_string()
+                } else if hl.label().is_some() {
+                    label_number += 1;
+                    if compact {
+                        label_number.to_string()
+                    } else {
+                        chars.underbar.to_string()
+                    }
+                } else {
+                    underline.to_string()
+                };
+                // Numbers with more than one digit are centered on the span
+                // too, running past its end if it's too short.
+                let marker_width = marker.chars().count();
+                let marker_start = vbar_offset.saturating_sub(marker_width / 2).max(start);
+                let marker_end = marker_start + marker_width;
+                let num_left = marker_start - start;
+                let num_right = end.saturating_sub(marker_end);
                 underlines.push_str(
                     &format!(
                         "{:width$}{}{}{}",
                         "",
-                        chars.underline.to_string().repeat(num_left),
-                        if hl.len() == 0 {
-                            chars.uarrow
-                        } else if hl.label().is_some() {
-                            chars.underbar
-                        } else {
-                            chars.underline
-                        },
-                        chars.underline.to_string().repeat(num_right),
+                        underline.to_string().repeat(num_left),
+                        marker,
+                        underline.to_string().repeat(num_right),
                         width = start.saturating_sub(highest),
                     )
                     .style(hl.style)
                     .to_string(),
                 );
-                highest = std::cmp::max(highest, end);
+                highest = std::cmp::max(highest, std::cmp::max(end, marker_end));
 
                 (hl, vbar_offset)
             })
             .collect();
         writeln!(f, "{}", underlines)?;
 
+        if compact {
+            let labels = single_liners
+                .iter()
+                .filter_map(|hl| Some((hl, hl.label()?)));
+            for (i, (hl, label)) in labels.enumerate() {
+                self.write_no_linum(f, linum_width)?;
+                self.render_highlight_gutter(
+                    f,
+                    max_gutter,
+                    line,
+                    all_highlights,
+                    LabelRenderMode::SingleLine,
+                )?;
+                let text = format!("{} {}", LabelRef(i + 1), label.replace('\n', " "));
+                writeln!(f, "{}", text.style(hl.style))?;
+            }
+            return Ok(());
+        }
+
         for hl in single_liners.iter().rev() {
             if let Some(label) = hl.label_parts() {
                 if label.len() == 1 {
@@ -1173,6 +2754,42 @@ impl GraphicalReportHandler {
         Ok(())
     }
 
+    fn render_multi_line_end_column(
+        &self,
+        f: &mut impl fmt::Write,
+        end_width: Option<usize>,
+        style: Style,
+        render_mode: LabelRenderMode,
+    ) -> fmt::Result {
+        let end_width = match end_width {
+            Some(end_width) => end_width,
+            None => return Ok(()),
+        };
+        let chars = &self.theme.characters;
+        match render_mode {
+            LabelRenderMode::SingleLine => {
+                let line = chars.hbar.to_string().repeat(end_width.saturating_sub(1));
+                write!(f, "{}{}", line.style(style), chars.mbot.style(style))?;
+            }
+            LabelRenderMode::MultiLineFirst => {
+                // The gutter leaves a column for the rcross, which now comes
+                // after the marker instead.
+                let line = chars.hbar.to_string().repeat(end_width);
+                write!(
+                    f,
+                    "{}{}{}",
+                    line.style(style),
+                    chars.mbot.style(style),
+                    chars.hbar.style(style)
+                )?;
+            }
+            LabelRenderMode::MultiLineRest => {
+                write!(f, "{:width$}", "", width = end_width + 2)?;
+            }
+        }
+        Ok(())
+    }
+
     fn render_multi_line_end_single(
         &self,
         f: &mut impl fmt::Write,
@@ -1199,9 +2816,35 @@ impl GraphicalReportHandler {
         &'a self,
         source: &'a dyn SourceCode,
         context_span: &'a SourceSpan,
+    ) -> Result<(Box<dyn SpanContents<'a> + 'a>, Vec<Line>), fmt::Error> {
+        if self.context_lines > 0 {
+            return self.get_lines_with_context(source, context_span, self.context_lines);
+        }
+        // Without any context, `read_span` returns just the span itself,
+        // which may start and end mid-line. Read a line around it instead,
+        // and drop the lines the span doesn't touch.
+        let (contents, mut lines) = self.get_lines_with_context(source, context_span, 1)?;
+        let start = context_span.offset();
+        let end = start + context_span.len().saturating_sub(1);
+        let last = lines.pop();
+        lines.retain(|line| line.offset <= end && start < line.offset + line.length);
+        if let Some(last) = last {
+            // A span at the very end of the source touches the last line.
+            if lines.is_empty() || (last.offset <= end && start <= last.offset + last.length) {
+                lines.push(last);
+            }
+        }
+        Ok((contents, lines))
+    }
+
+    fn get_lines_with_context<'a>(
+        &'a self,
+        source: &'a dyn SourceCode,
+        context_span: &'a SourceSpan,
+        context_lines: usize,
     ) -> Result<(Box<dyn SpanContents<'a> + 'a>, Vec<Line>), fmt::Error> {
         let context_data = source
-            .read_span(context_span, self.context_lines, self.context_lines)
+            .read_span(context_span, context_lines, context_lines)
             .map_err(|_| fmt::Error)?;
         let context = String::from_utf8_lossy(context_data.data());
         let mut line = context_data.line();
@@ -1216,14 +2859,12 @@ impl GraphicalReportHandler {
             let mut at_end_of_file = false;
             match char {
                 '\r' => {
+                    // Both `\r\n` and a lone (classic Mac) `\r` end a line.
                     if iter.next_if_eq(&'\n').is_some() {
                         offset += 1;
-                        line += 1;
-                        column = 0;
-                    } else {
-                        line_str.push(char);
-                        column += 1;
                     }
+                    line += 1;
+                    column = 0;
                     at_end_of_file = iter.peek().is_none();
                 }
                 '\n' => {
@@ -1257,7 +2898,7 @@ impl GraphicalReportHandler {
 }
 
 impl ReportHandler for GraphicalReportHandler {
-    fn debug(&self, diagnostic: &(dyn Diagnostic), f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    fn debug(&self, diagnostic: &dyn Diagnostic, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         if f.alternate() {
             return fmt::Debug::fmt(diagnostic, f);
         }
@@ -1270,6 +2911,43 @@ impl ReportHandler for GraphicalReportHandler {
 Support types
 */
 
+/// [`fmt::Write`] adapter that indents every non-empty line written to it.
+struct IndentWriter<'a, W> {
+    inner: &'a mut W,
+    indent: String,
+    at_line_start: bool,
+}
+
+impl<W: fmt::Write> fmt::Write for IndentWriter<'_, W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for line in s.split_inclusive('\n') {
+            if self.at_line_start && line != "\n" {
+                self.inner.write_str(&self.indent)?;
+            }
+            self.inner.write_str(line)?;
+            self.at_line_start = line.ends_with('\n');
+        }
+        Ok(())
+    }
+}
+
+/// Name of the source a related diagnostic points into, as reported by the
+/// [`SpanContents`] for its first label (or the start of the source, if it has
+/// no labels).
+fn related_source_name(
+    rel: &dyn Diagnostic,
+    parent_src: Option<&dyn SourceCode>,
+) -> Option<String> {
+    let src = rel.source_code().or(parent_src)?;
+    let span = rel
+        .labels()
+        .and_then(|mut labels| labels.next())
+        .map(|label| *label.inner())
+        .unwrap_or_else(|| SourceSpan::from(0));
+    let contents = src.read_span(&span, 0, 0).ok()?;
+    contents.name().map(String::from)
+}
+
 #[derive(PartialEq, Debug)]
 enum LabelRenderMode {
     /// we're rendering a single line label (or not rendering in any special way)
@@ -1353,6 +3031,7 @@ struct FancySpan {
     label: Option<Vec<String>>,
     span: SourceSpan,
     style: Style,
+    primary: bool,
 }
 
 impl PartialEq for FancySpan {
@@ -1366,11 +3045,12 @@ fn split_label(v: String) -> Vec<String> {
 }
 
 impl FancySpan {
-    fn new(label: Option<String>, span: SourceSpan, style: Style) -> Self {
+    fn new(label: Option<String>, span: SourceSpan, style: Style, primary: bool) -> Self {
         FancySpan {
             label: label.map(split_label),
             span,
             style,
+            primary,
         }
     }
 
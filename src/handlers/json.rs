@@ -1,7 +1,8 @@
 use std::fmt::{self, Write};
 
 use crate::{
-    diagnostic_chain::DiagnosticChain, protocol::Diagnostic, ReportHandler, Severity, SourceCode,
+    diagnostic_chain::DiagnosticChain, handlers::owned_source_code, protocol::Diagnostic,
+    ReportHandler, Severity, SourceCode,
 };
 
 /**
@@ -60,7 +61,7 @@ impl JSONReportHandler {
     pub fn render_report(
         &self,
         f: &mut impl fmt::Write,
-        diagnostic: &(dyn Diagnostic),
+        diagnostic: &dyn Diagnostic,
     ) -> fmt::Result {
         self._render_report(f, diagnostic, None)
     }
@@ -68,10 +69,20 @@ impl JSONReportHandler {
     fn _render_report(
         &self,
         f: &mut impl fmt::Write,
-        diagnostic: &(dyn Diagnostic),
+        diagnostic: &dyn Diagnostic,
         parent_src: Option<&dyn SourceCode>,
     ) -> fmt::Result {
         write!(f, r#"{{"message": "{}","#, escape(&diagnostic.to_string()))?;
+        if let Some(title) = diagnostic.title() {
+            write!(f, r#""title": "{}","#, escape(&title.to_string()))?;
+        }
+        if let Some(long_message) = diagnostic.long_message() {
+            write!(
+                f,
+                r#""long_message": "{}","#,
+                escape(&long_message.to_string())
+            )?;
+        }
         if let Some(code) = diagnostic.code() {
             write!(f, r#""code": "{}","#, escape(&code.to_string()))?;
         }
@@ -106,7 +117,11 @@ impl JSONReportHandler {
         if let Some(help) = diagnostic.help() {
             write!(f, r#""help": "{}","#, escape(&help.to_string()))?;
         }
-        let src = diagnostic.source_code().or(parent_src);
+        let owned_src = owned_source_code(diagnostic);
+        let src = diagnostic
+            .source_code()
+            .or(owned_src.as_deref())
+            .or(parent_src);
         if let Some(src) = src {
             self.render_snippets(f, diagnostic, src)?;
         }
@@ -154,7 +169,7 @@ impl JSONReportHandler {
     fn render_snippets(
         &self,
         f: &mut impl fmt::Write,
-        diagnostic: &(dyn Diagnostic),
+        diagnostic: &dyn Diagnostic,
         source: &dyn SourceCode,
     ) -> fmt::Result {
         if let Some(mut labels) = diagnostic.labels() {
@@ -170,7 +185,7 @@ impl JSONReportHandler {
 }
 
 impl ReportHandler for JSONReportHandler {
-    fn debug(&self, diagnostic: &(dyn Diagnostic), f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    fn debug(&self, diagnostic: &dyn Diagnostic, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         self.render_report(f, diagnostic)
     }
 }
@@ -0,0 +1,96 @@
+use std::io::{self, Write};
+
+use crate::{GraphicalReportHandler, OwnedDiagnostic};
+
+/**
+[`io::Write`] adapter that renders serialized diagnostics graphically.
+
+Everything written to it is split into lines. Lines holding a JSON-serialized
+[`OwnedDiagnostic`] are rendered with a [`GraphicalReportHandler`] and the
+result written to the inner writer. Every other line is passed through as-is,
+so this can sit between a terminal and a tool that's only partly been
+migrated to emitting diagnostics as JSON.
+
+A trailing line without a newline is held back until more is written, or
+until [`JsonToGraphical::finish`] is called. Requires the `json` feature.
+
+```rust
+use std::io::Write;
+
+use miette::{GraphicalReportHandler, GraphicalTheme, JsonToGraphical, OwnedDiagnostic};
+
+let diag = OwnedDiagnostic::new("oops!");
+let mut writer = JsonToGraphical::new(Vec::new()).with_handler(
+    GraphicalReportHandler::new_themed(GraphicalTheme::unicode_nocolor()),
+);
+writeln!(writer, "{}", serde_json::to_string(&diag).unwrap()).unwrap();
+let out = String::from_utf8(writer.finish().unwrap()).unwrap();
+assert_eq!(out.trim(), "× oops!");
+```
+*/
+#[derive(Debug)]
+pub struct JsonToGraphical<W: Write> {
+    inner: W,
+    handler: GraphicalReportHandler,
+    buffer: Vec<u8>,
+}
+
+impl<W: Write> JsonToGraphical<W> {
+    /// Create a new `JsonToGraphical` that writes to `inner`, rendering with
+    /// the default [`GraphicalReportHandler`].
+    pub fn new(inner: W) -> Self {
+        Self {
+            inner,
+            handler: GraphicalReportHandler::new(),
+            buffer: Vec::new(),
+        }
+    }
+
+    /// Set the [`GraphicalReportHandler`] used to render diagnostics.
+    pub fn with_handler(mut self, handler: GraphicalReportHandler) -> Self {
+        self.handler = handler;
+        self
+    }
+
+    /// Handles any buffered partial line, flushes, and returns the inner
+    /// writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        if !self.buffer.is_empty() {
+            let line = std::mem::take(&mut self.buffer);
+            self.write_line(&line)?;
+        }
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+
+    fn write_line(&mut self, line: &[u8]) -> io::Result<()> {
+        let diagnostic = serde_json::from_slice::<OwnedDiagnostic>(line);
+        match diagnostic {
+            Ok(diagnostic) => {
+                let mut out = String::new();
+                self.handler
+                    .render_report(&mut out, &diagnostic)
+                    .map_err(|_| {
+                        io::Error::new(io::ErrorKind::Other, "failed to render diagnostic")
+                    })?;
+                self.inner.write_all(out.as_bytes())
+            }
+            Err(_) => self.inner.write_all(line),
+        }
+    }
+}
+
+impl<W: Write> Write for JsonToGraphical<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+        while let Some(newline) = self.buffer.iter().position(|b| *b == b'\n') {
+            let line = self.buffer.drain(..=newline).collect::<Vec<_>>();
+            self.write_line(&line)?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
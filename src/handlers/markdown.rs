@@ -0,0 +1,215 @@
+use std::fmt::{self, Write};
+
+use unicode_width::UnicodeWidthStr;
+
+use crate::{
+    diagnostic_chain::DiagnosticChain,
+    handlers::{gnu::severity_name, owned_source_code},
+    protocol::Diagnostic,
+    LabeledSpan, ReportHandler, SourceCode,
+};
+
+/**
+[`ReportHandler`] that renders diagnostics as (GitHub-flavored) Markdown,
+for pasting into bug reports and issue templates.
+
+The message is rendered after its bold severity and code, followed by the
+cause chain. Each label gets a fenced code block with its source snippet,
+and help text and related diagnostics are rendered as a bullet list.
+Characters in messages that Markdown or HTML would interpret are escaped.
+*/
+#[derive(Debug, Clone)]
+pub struct MarkdownReportHandler {
+    context_lines: usize,
+}
+
+impl MarkdownReportHandler {
+    /// Create a new [`MarkdownReportHandler`].
+    pub const fn new() -> Self {
+        Self { context_lines: 1 }
+    }
+
+    /// Sets the number of lines of context to show around each label.
+    pub const fn with_context_lines(mut self, lines: usize) -> Self {
+        self.context_lines = lines;
+        self
+    }
+}
+
+impl Default for MarkdownReportHandler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+struct Escape<'a>(&'a str);
+
+impl fmt::Display for Escape<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for c in self.0.chars() {
+            match c {
+                '<' => f.write_str("&lt;")?,
+                '>' => f.write_str("&gt;")?,
+                '&' => f.write_str("&amp;")?,
+                '\\' | '`' | '*' | '_' | '[' | ']' | '|' | '~' | '#' => {
+                    f.write_char('\\')?;
+                    f.write_char(c)?;
+                }
+                _ => f.write_char(c)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+const fn escape(input: &'_ str) -> Escape<'_> {
+    Escape(input)
+}
+
+impl MarkdownReportHandler {
+    /// Render a [`Diagnostic`]. This function is mostly internal and meant to
+    /// be called by the toplevel [`ReportHandler`] handler, but is made public
+    /// to make it easier (possible) to test in isolation from global state.
+    pub fn render_report(
+        &self,
+        f: &mut impl fmt::Write,
+        diagnostic: &dyn Diagnostic,
+    ) -> fmt::Result {
+        let owned_src = owned_source_code(diagnostic);
+        let src = diagnostic.source_code().or(owned_src.as_deref());
+
+        writeln!(f, "{}", Heading(diagnostic))?;
+        if let Some(cause_iter) = diagnostic
+            .diagnostic_source()
+            .map(DiagnosticChain::from_diagnostic)
+            .or_else(|| diagnostic.source().map(DiagnosticChain::from_stderror))
+        {
+            writeln!(f)?;
+            writeln!(f, "Caused by:")?;
+            for error in cause_iter {
+                writeln!(f, "- {}", escape(&error.to_string()))?;
+            }
+        }
+
+        if let (Some(src), Some(labels)) = (src, diagnostic.labels()) {
+            let mut labels = labels.collect::<Vec<_>>();
+            labels.sort_unstable_by_key(|label| label.offset());
+            for label in &labels {
+                writeln!(f)?;
+                self.render_snippet(f, src, label)?;
+            }
+        }
+
+        let mut bullets = Vec::new();
+        if let Some(help) = diagnostic.help() {
+            bullets.push(format!("**help**: {}", escape(&help.to_string())));
+        }
+        if let Some(related) = diagnostic.related() {
+            bullets.extend(related.map(|rel| Heading(rel).to_string()));
+        }
+        if !bullets.is_empty() {
+            writeln!(f)?;
+            for bullet in bullets {
+                writeln!(f, "- {}", bullet)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn render_snippet(
+        &self,
+        f: &mut impl fmt::Write,
+        source: &dyn SourceCode,
+        label: &LabeledSpan,
+    ) -> fmt::Result {
+        let contents = source
+            .read_span(label.inner(), self.context_lines, self.context_lines)
+            .map_err(|_| fmt::Error)?;
+        let location = source
+            .read_span(label.inner(), 0, 0)
+            .map_err(|_| fmt::Error)?;
+        let text = String::from_utf8_lossy(contents.data());
+
+        let name = location.name().unwrap_or("<unknown>");
+        let line = location.line() + 1;
+        let column = location.column() + 1;
+        writeln!(f, "`{}:{}:{}`:", escape_code(name), line, column)?;
+
+        // Use a fence that's longer than any run of backticks in the snippet,
+        // so the source can't end it early.
+        let mut longest_run = 0;
+        let mut run = 0;
+        for c in text.chars() {
+            run = if c == '`' { run + 1 } else { 0 };
+            longest_run = longest_run.max(run);
+        }
+        let fence = "`".repeat(std::cmp::max(3, longest_run + 1));
+        writeln!(f, "{}", fence)?;
+
+        let linum_width = (contents.line() + text.split_inclusive('\n').count())
+            .to_string()
+            .len();
+        let mut offset = contents.span().offset();
+        let mut underlined = false;
+        for (i, line_text) in text.split_inclusive('\n').enumerate() {
+            let line_end = offset + line_text.len();
+            // A label at the very end of the snippet belongs to its last line.
+            let has_label_start = label.offset() < line_end
+                || (label.offset() == line_end && !line_text.ends_with('\n'));
+            let line_text = line_text.trim_end_matches(['\n', '\r']);
+            writeln!(
+                f,
+                "{:>width$} | {}",
+                contents.line() + i + 1,
+                line_text,
+                width = linum_width
+            )?;
+            if !underlined && has_label_start {
+                underlined = true;
+                let start = label.offset().saturating_sub(offset).min(line_text.len());
+                let end = (label.offset() + label.len())
+                    .saturating_sub(offset)
+                    .clamp(start, line_text.len());
+                let indent = line_text.get(..start).map_or(0, |s| s.width());
+                let width = line_text.get(start..end).map_or(0, |s| s.width());
+                let mut underline = format!(
+                    "{} | {}{}",
+                    " ".repeat(linum_width),
+                    " ".repeat(indent),
+                    "^".repeat(width.max(1))
+                );
+                if let Some(text) = label.label() {
+                    write!(underline, " {}", text)?;
+                }
+                writeln!(f, "{}", underline)?;
+            }
+            offset = line_end;
+        }
+        writeln!(f, "{}", fence)
+    }
+}
+
+/// Inline code spans can't contain their delimiters, so swap them out.
+fn escape_code(input: &str) -> String {
+    input.replace('`', "'")
+}
+
+/// Bold severity and code of a diagnostic, followed by its message.
+struct Heading<'a>(&'a dyn Diagnostic);
+
+impl fmt::Display for Heading<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = severity_name(self.0);
+        match self.0.code() {
+            Some(code) => write!(f, "**{}\\[{}\\]**", severity, escape(&code.to_string()))?,
+            None => write!(f, "**{}**", severity)?,
+        }
+        write!(f, ": {}", escape(&self.0.to_string()))
+    }
+}
+
+impl ReportHandler for MarkdownReportHandler {
+    fn debug(&self, diagnostic: &dyn Diagnostic, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.render_report(f, diagnostic)
+    }
+}
@@ -5,20 +5,45 @@ Reporters included with `miette`.
 #[allow(unreachable_pub)]
 pub use debug::*;
 #[allow(unreachable_pub)]
+pub use gnu::*;
+#[allow(unreachable_pub)]
 #[cfg(feature = "fancy-base")]
 pub use graphical::*;
 #[allow(unreachable_pub)]
 pub use json::*;
 #[allow(unreachable_pub)]
+#[cfg(all(feature = "fancy-base", feature = "json"))]
+pub use json_to_graphical::*;
+#[allow(unreachable_pub)]
+pub use markdown::*;
+#[allow(unreachable_pub)]
 pub use narratable::*;
 #[allow(unreachable_pub)]
+pub use short::*;
+#[allow(unreachable_pub)]
 #[cfg(feature = "fancy-base")]
 pub use theme::*;
 
 mod debug;
+mod gnu;
 #[cfg(feature = "fancy-base")]
 mod graphical;
 mod json;
+#[cfg(all(feature = "fancy-base", feature = "json"))]
+mod json_to_graphical;
+mod markdown;
 mod narratable;
+mod short;
 #[cfg(feature = "fancy-base")]
 mod theme;
+
+use crate::{Diagnostic, SourceCode};
+
+/// Owned source code of `diagnostic`, for when it doesn't have borrowed
+/// [`Diagnostic::source_code`] to use instead.
+pub(crate) fn owned_source_code(diagnostic: &dyn Diagnostic) -> Option<Box<dyn SourceCode + '_>> {
+    match diagnostic.source_code() {
+        Some(_) => None,
+        None => diagnostic.source_code_owned(),
+    }
+}
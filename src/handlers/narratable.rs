@@ -3,6 +3,7 @@ use std::fmt;
 use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
 use crate::diagnostic_chain::DiagnosticChain;
+use crate::handlers::owned_source_code;
 use crate::protocol::{Diagnostic, Severity};
 use crate::{LabeledSpan, MietteError, ReportHandler, SourceCode, SourceSpan, SpanContents};
 
@@ -69,92 +70,143 @@ impl NarratableReportHandler {
     pub fn render_report(
         &self,
         f: &mut impl fmt::Write,
-        diagnostic: &(dyn Diagnostic),
+        diagnostic: &dyn Diagnostic,
     ) -> fmt::Result {
-        self.render_header(f, diagnostic)?;
-        if self.with_cause_chain {
-            self.render_causes(f, diagnostic)?;
-        }
-        let src = diagnostic.source_code();
-        self.render_snippets(f, diagnostic, src)?;
-        self.render_footer(f, diagnostic)?;
-        self.render_related(f, diagnostic, src)?;
+        let report = self.compute(diagnostic)?;
+        self.render_header(f, &report)?;
+        self.render_body(f, &report, false)?;
         if let Some(footer) = &self.footer {
             writeln!(f, "{}", footer)?;
         }
         Ok(())
     }
 
-    fn render_header(&self, f: &mut impl fmt::Write, diagnostic: &(dyn Diagnostic)) -> fmt::Result {
-        writeln!(f, "{}", diagnostic)?;
-        let severity = match diagnostic.severity() {
-            Some(Severity::Error) | None => "error",
-            Some(Severity::Warning) => "warning",
-            Some(Severity::Advice) => "advice",
+    /// Compute the structured [`NarrativeReport`] for a [`Diagnostic`]. This
+    /// is what [`NarratableReportHandler::render_report`] renders, and is
+    /// useful for inspecting or re-rendering the narration without having to
+    /// parse its text.
+    ///
+    /// Returns an error if a label's source can't be read, just like
+    /// [`NarratableReportHandler::render_report`].
+    pub fn compute(&self, diagnostic: &dyn Diagnostic) -> Result<NarrativeReport, fmt::Error> {
+        self.narrate(diagnostic, None, self.with_cause_chain)
+    }
+
+    fn narrate(
+        &self,
+        diagnostic: &dyn Diagnostic,
+        parent_src: Option<&dyn SourceCode>,
+        with_cause_chain: bool,
+    ) -> Result<NarrativeReport, fmt::Error> {
+        let owned_src = owned_source_code(diagnostic);
+        let source_code = diagnostic
+            .source_code()
+            .or(owned_src.as_deref())
+            .or(parent_src);
+        let causes = if with_cause_chain {
+            self.narrate_causes(diagnostic)
+        } else {
+            Vec::new()
         };
-        writeln!(f, "    Diagnostic severity: {}", severity)?;
-        Ok(())
+        let related = match diagnostic.related() {
+            Some(related) => Some(
+                related
+                    .map(|rel| self.narrate(rel, source_code, true))
+                    .collect::<Result<Vec<_>, _>>()?,
+            ),
+            None => None,
+        };
+        Ok(NarrativeReport {
+            title: diagnostic.to_string(),
+            severity: diagnostic.severity().unwrap_or(Severity::Error),
+            causes,
+            snippets: self.narrate_snippets(diagnostic, source_code)?,
+            help: diagnostic.help().map(|help| help.to_string()),
+            code: diagnostic.code().map(|code| code.to_string()),
+            url: diagnostic.url().map(|url| url.to_string()),
+            related,
+        })
     }
 
-    fn render_causes(&self, f: &mut impl fmt::Write, diagnostic: &(dyn Diagnostic)) -> fmt::Result {
-        if let Some(cause_iter) = diagnostic
+    fn narrate_causes(&self, diagnostic: &dyn Diagnostic) -> Vec<String> {
+        diagnostic
             .diagnostic_source()
             .map(DiagnosticChain::from_diagnostic)
             .or_else(|| diagnostic.source().map(DiagnosticChain::from_stderror))
-        {
-            for error in cause_iter {
-                writeln!(f, "    Caused by: {}", error)?;
-            }
-        }
+            .map(|cause_iter| cause_iter.map(|error| error.to_string()).collect())
+            .unwrap_or_default()
+    }
 
+    fn render_header(&self, f: &mut impl fmt::Write, report: &NarrativeReport) -> fmt::Result {
+        writeln!(f, "{}", report.title)?;
+        writeln!(f, "    Diagnostic severity: {}", report.severity)?;
         Ok(())
     }
 
-    fn render_footer(&self, f: &mut impl fmt::Write, diagnostic: &(dyn Diagnostic)) -> fmt::Result {
-        if let Some(help) = diagnostic.help() {
+    fn render_body(
+        &self,
+        f: &mut impl fmt::Write,
+        report: &NarrativeReport,
+        nested: bool,
+    ) -> fmt::Result {
+        for cause in &report.causes {
+            writeln!(f, "    Caused by: {}", cause)?;
+        }
+        for snippet in &report.snippets {
+            self.render_snippet(f, snippet)?;
+        }
+        self.render_footer(f, report)?;
+        self.render_related(f, report, nested)
+    }
+
+    fn render_footer(&self, f: &mut impl fmt::Write, report: &NarrativeReport) -> fmt::Result {
+        if let Some(help) = &report.help {
             writeln!(f, "diagnostic help: {}", help)?;
         }
-        if let Some(code) = diagnostic.code() {
+        if let Some(code) = &report.code {
             writeln!(f, "diagnostic code: {}", code)?;
         }
-        if let Some(url) = diagnostic.url() {
+        if let Some(url) = &report.url {
             writeln!(f, "For more details, see:\n{}", url)?;
         }
         Ok(())
     }
 
+    /// Renders `report`'s related diagnostics, and theirs in turn. Those
+    /// nested inside another related diagnostic are introduced with
+    /// "Additionally:", so they can be told apart from their parent's
+    /// siblings.
     fn render_related(
         &self,
         f: &mut impl fmt::Write,
-        diagnostic: &(dyn Diagnostic),
-        parent_src: Option<&dyn SourceCode>,
+        report: &NarrativeReport,
+        nested: bool,
     ) -> fmt::Result {
-        if let Some(related) = diagnostic.related() {
+        if let Some(related) = &report.related {
             writeln!(f)?;
             for rel in related {
-                match rel.severity() {
-                    Some(Severity::Error) | None => write!(f, "Error: ")?,
-                    Some(Severity::Warning) => write!(f, "Warning: ")?,
-                    Some(Severity::Advice) => write!(f, "Advice: ")?,
+                if nested {
+                    write!(f, "Additionally: ")?;
+                }
+                match rel.severity {
+                    Severity::Error => write!(f, "Error: ")?,
+                    Severity::Warning => write!(f, "Warning: ")?,
+                    Severity::Advice => write!(f, "Advice: ")?,
                 };
                 self.render_header(f, rel)?;
                 writeln!(f)?;
-                self.render_causes(f, rel)?;
-                let src = rel.source_code().or(parent_src);
-                self.render_snippets(f, rel, src)?;
-                self.render_footer(f, rel)?;
-                self.render_related(f, rel, src)?;
+                self.render_body(f, rel, true)?;
             }
         }
         Ok(())
     }
 
-    fn render_snippets(
+    fn narrate_snippets(
         &self,
-        f: &mut impl fmt::Write,
-        diagnostic: &(dyn Diagnostic),
+        diagnostic: &dyn Diagnostic,
         source_code: Option<&dyn SourceCode>,
-    ) -> fmt::Result {
+    ) -> Result<Vec<NarrativeSnippet>, fmt::Error> {
+        let mut snippets = Vec::new();
         if let Some(source) = source_code {
             if let Some(labels) = diagnostic.labels() {
                 let mut labels = labels.collect::<Vec<_>>();
@@ -210,62 +262,87 @@ impl NarratableReportHandler {
                         }
                     }
                     for (ctx, _) in contexts {
-                        self.render_context(f, source, &ctx, &labels[..])?;
+                        snippets.push(self.narrate_context(source, &ctx, &labels[..])?);
                     }
                 }
             }
         }
-        Ok(())
+        Ok(snippets)
     }
 
-    fn render_context(
+    fn narrate_context(
         &self,
-        f: &mut impl fmt::Write,
         source: &dyn SourceCode,
         context: &LabeledSpan,
         labels: &[LabeledSpan],
-    ) -> fmt::Result {
+    ) -> Result<NarrativeSnippet, fmt::Error> {
         let (contents, lines) = self.get_lines(source, context.inner())?;
+        let lines = lines
+            .into_iter()
+            .map(|line| {
+                let labels = labels
+                    .iter()
+                    .filter_map(|l| {
+                        line.span_attach(l.inner()).map(|attach| NarrativeLabel {
+                            attach,
+                            label: l.label().map(String::from),
+                        })
+                    })
+                    .collect();
+                NarrativeLine {
+                    line_number: line.line_number,
+                    text: line.text,
+                    labels,
+                }
+            })
+            .collect();
+        Ok(NarrativeSnippet {
+            name: contents.name().map(String::from),
+            line: contents.line() + 1,
+            column: contents.column() + 1,
+            lines,
+        })
+    }
+
+    fn render_snippet(&self, f: &mut impl fmt::Write, snippet: &NarrativeSnippet) -> fmt::Result {
         write!(f, "Begin snippet")?;
-        if let Some(filename) = contents.name() {
+        if let Some(filename) = &snippet.name {
             write!(f, " for {}", filename,)?;
         }
         writeln!(
             f,
             " starting at line {}, column {}",
-            contents.line() + 1,
-            contents.column() + 1
+            snippet.line, snippet.column
         )?;
         writeln!(f)?;
-        for line in &lines {
+        for line in &snippet.lines {
             writeln!(f, "snippet line {}: {}", line.line_number, line.text)?;
-            let relevant = labels
-                .iter()
-                .filter_map(|l| line.span_attach(l.inner()).map(|a| (a, l)));
-            for (attach, label) in relevant {
-                match attach {
-                    SpanAttach::Contained { col_start, col_end } if col_start == col_end => {
+            for label in &line.labels {
+                match label.attach {
+                    NarrativeLabelAttach::Contained { col_start, col_end }
+                        if col_start == col_end =>
+                    {
                         write!(
                             f,
                             "    label at line {}, column {}",
                             line.line_number, col_start,
                         )?;
                     }
-                    SpanAttach::Contained { col_start, col_end } => {
+                    NarrativeLabelAttach::Contained { col_start, col_end } => {
                         write!(
                             f,
                             "    label at line {}, columns {} to {}",
                             line.line_number, col_start, col_end,
                         )?;
                     }
-                    SpanAttach::Starts { col_start } => {
+                    NarrativeLabelAttach::Starts { col_start } => {
                         write!(
                             f,
                             "    label starting at line {}, column {}",
                             line.line_number, col_start,
                         )?;
                     }
-                    SpanAttach::Ends { col_end } => {
+                    NarrativeLabelAttach::Ends { col_end } => {
                         write!(
                             f,
                             "    label ending at line {}, column {}",
@@ -273,7 +350,7 @@ impl NarratableReportHandler {
                         )?;
                     }
                 }
-                if let Some(label) = label.label() {
+                if let Some(label) = &label.label {
                     write!(f, ": {}", label)?;
                 }
                 writeln!(f)?;
@@ -303,14 +380,12 @@ impl NarratableReportHandler {
             let mut at_end_of_file = false;
             match char {
                 '\r' => {
+                    // Both `\r\n` and a lone (classic Mac) `\r` end a line.
                     if iter.next_if_eq(&'\n').is_some() {
                         offset += 1;
-                        line += 1;
-                        column = 0;
-                    } else {
-                        line_str.push(char);
-                        column += 1;
                     }
+                    line += 1;
+                    column = 0;
                     at_end_of_file = iter.peek().is_none();
                 }
                 '\n' => {
@@ -344,7 +419,7 @@ impl NarratableReportHandler {
 }
 
 impl ReportHandler for NarratableReportHandler {
-    fn debug(&self, diagnostic: &(dyn Diagnostic), f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    fn debug(&self, diagnostic: &dyn Diagnostic, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         if f.alternate() {
             return fmt::Debug::fmt(diagnostic, f);
         }
@@ -357,6 +432,85 @@ impl ReportHandler for NarratableReportHandler {
 Support types
 */
 
+/// Structured form of the narration produced by [`NarratableReportHandler`],
+/// as returned by [`NarratableReportHandler::compute`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NarrativeReport {
+    /// The diagnostic's message.
+    pub title: String,
+    /// The diagnostic's severity, defaulting to [`Severity::Error`].
+    pub severity: Severity,
+    /// Messages of the diagnostic's cause chain, outermost first.
+    pub causes: Vec<String>,
+    /// Snippets of source code the diagnostic's labels point into.
+    pub snippets: Vec<NarrativeSnippet>,
+    /// The diagnostic's help text, if any.
+    pub help: Option<String>,
+    /// The diagnostic's code, if any.
+    pub code: Option<String>,
+    /// The diagnostic's URL, if any.
+    pub url: Option<String>,
+    /// Narrations of the diagnostic's related diagnostics, or `None` if it
+    /// doesn't provide any.
+    pub related: Option<Vec<NarrativeReport>>,
+}
+
+/// A snippet of source code within a [`NarrativeReport`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NarrativeSnippet {
+    /// The name of the source the snippet comes from, if it has one.
+    pub name: Option<String>,
+    /// The (1-based) line the snippet starts at.
+    pub line: usize,
+    /// The (1-based) column the snippet starts at.
+    pub column: usize,
+    /// The lines making up the snippet.
+    pub lines: Vec<NarrativeLine>,
+}
+
+/// A single line of a [`NarrativeSnippet`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NarrativeLine {
+    /// The (1-based) line number.
+    pub line_number: usize,
+    /// The text of the line, without its line ending.
+    pub text: String,
+    /// The labels that touch this line.
+    pub labels: Vec<NarrativeLabel>,
+}
+
+/// A label attached to a [`NarrativeLine`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NarrativeLabel {
+    /// Where on the line the label is.
+    pub attach: NarrativeLabelAttach,
+    /// The label's text, if any.
+    pub label: Option<String>,
+}
+
+/// How a [`NarrativeLabel`] relates to the line it's attached to. Columns are
+/// 1-based and inclusive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NarrativeLabelAttach {
+    /// The label starts and ends on this line.
+    Contained {
+        /// The column the label starts at.
+        col_start: usize,
+        /// The column the label ends at.
+        col_end: usize,
+    },
+    /// The label starts on this line and ends on a later one.
+    Starts {
+        /// The column the label starts at.
+        col_start: usize,
+    },
+    /// The label started on an earlier line and ends on this one.
+    Ends {
+        /// The column the label ends at.
+        col_end: usize,
+    },
+}
+
 struct Line {
     line_number: usize,
     offset: usize,
@@ -364,12 +518,6 @@ struct Line {
     at_end_of_file: bool,
 }
 
-enum SpanAttach {
-    Contained { col_start: usize, col_end: usize },
-    Starts { col_start: usize },
-    Ends { col_end: usize },
-}
-
 /// Returns column at offset, and nearest boundary if offset is in the middle of
 /// the character
 fn safe_get_column(text: &str, offset: usize, start: bool) -> usize {
@@ -392,7 +540,7 @@ fn safe_get_column(text: &str, offset: usize, start: bool) -> usize {
 }
 
 impl Line {
-    fn span_attach(&self, span: &SourceSpan) -> Option<SpanAttach> {
+    fn span_attach(&self, span: &SourceSpan) -> Option<NarrativeLabelAttach> {
         let span_end = span.offset() + span.len();
         let line_end = self.offset + self.text.len();
 
@@ -408,15 +556,15 @@ impl Line {
                 // while col_end refers to the exact character, so -1
                 safe_get_column(&self.text, span_end - self.offset, false)
             };
-            return Some(SpanAttach::Contained { col_start, col_end });
+            return Some(NarrativeLabelAttach::Contained { col_start, col_end });
         }
         if start_after && span.offset() <= line_end {
             let col_start = safe_get_column(&self.text, span.offset() - self.offset, true);
-            return Some(SpanAttach::Starts { col_start });
+            return Some(NarrativeLabelAttach::Starts { col_start });
         }
         if end_before && span_end >= self.offset {
             let col_end = safe_get_column(&self.text, span_end - self.offset, false);
-            return Some(SpanAttach::Ends { col_end });
+            return Some(NarrativeLabelAttach::Ends { col_end });
         }
         None
     }
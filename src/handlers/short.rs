@@ -0,0 +1,94 @@
+use std::fmt;
+
+use crate::{
+    handlers::{
+        gnu::{label_locations, severity_name},
+        owned_source_code,
+    },
+    protocol::Diagnostic,
+    ReportHandler, SourceCode,
+};
+
+/**
+[`ReportHandler`] that renders each diagnostic as a single
+`file:line:col: severity[code]: message` line, like rustc's
+`--error-format=short`, for editors that expect such one-liners.
+
+The location is that of the diagnostic's primary label (or its first label,
+by offset). Diagnostics without labels are rendered as
+`severity[code]: message`. Related diagnostics each get their own line. Help
+text isn't rendered; see [`GnuReportHandler`](crate::GnuReportHandler) for a
+more complete line-based format.
+*/
+#[derive(Debug, Clone, Default)]
+pub struct ShortReportHandler {
+    all_labels: bool,
+}
+
+impl ShortReportHandler {
+    /// Create a new [`ShortReportHandler`].
+    pub const fn new() -> Self {
+        Self { all_labels: false }
+    }
+
+    /// Whether to render one line per label, instead of one per diagnostic.
+    /// Defaults to `false`.
+    pub const fn with_all_labels(mut self, all_labels: bool) -> Self {
+        self.all_labels = all_labels;
+        self
+    }
+}
+
+impl ShortReportHandler {
+    /// Render a [`Diagnostic`]. This function is mostly internal and meant to
+    /// be called by the toplevel [`ReportHandler`] handler, but is made public
+    /// to make it easier (possible) to test in isolation from global state.
+    pub fn render_report(
+        &self,
+        f: &mut impl fmt::Write,
+        diagnostic: &dyn Diagnostic,
+    ) -> fmt::Result {
+        self.render_report_inner(f, diagnostic, None)
+    }
+
+    fn render_report_inner(
+        &self,
+        f: &mut impl fmt::Write,
+        diagnostic: &dyn Diagnostic,
+        parent_src: Option<&dyn SourceCode>,
+    ) -> fmt::Result {
+        let owned_src = owned_source_code(diagnostic);
+        let src = diagnostic
+            .source_code()
+            .or(owned_src.as_deref())
+            .or(parent_src);
+        let mut kind = severity_name(diagnostic).to_string();
+        if let Some(code) = diagnostic.code() {
+            kind = format!("{}[{}]", kind, code);
+        }
+
+        let mut locations = label_locations(diagnostic, src);
+        if !self.all_labels {
+            locations.truncate(1);
+        }
+        if locations.is_empty() {
+            writeln!(f, "{}: {}", kind, diagnostic)?;
+        }
+        for location in &locations {
+            writeln!(f, "{}{}: {}", location, kind, diagnostic)?;
+        }
+
+        if let Some(related) = diagnostic.related() {
+            for rel in related {
+                self.render_report_inner(f, rel, src)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl ReportHandler for ShortReportHandler {
+    fn debug(&self, diagnostic: &dyn Diagnostic, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.render_report(f, diagnostic)
+    }
+}
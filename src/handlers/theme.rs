@@ -1,6 +1,8 @@
 use std::io::IsTerminal;
 
 use owo_colors::Style;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
 /**
 Theme used by [`GraphicalReportHandler`](crate::GraphicalReportHandler) to
@@ -12,8 +14,15 @@ and the
 
 You can create your own custom graphical theme using this type, or you can use
 one of the predefined ones using the methods below.
+
+With the `serde` feature enabled, themes can also be loaded from (and saved
+to) config files. Styles are written as their named colors and attributes,
+for example `{ "fg": "cyan", "bold": true, "underline": true }`. Colors can
+be ANSI color names (`"red"`, `"bright red"`, `"default"`), xterm color
+numbers (`"208"`) or RGB hex codes (`"#ff1e1e"`).
 */
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct GraphicalTheme {
     /// Characters to be used for drawing.
     pub characters: ThemeCharacters,
@@ -73,32 +82,52 @@ impl Default for GraphicalTheme {
             _ if !std::io::stdout().is_terminal() || !std::io::stderr().is_terminal() => {
                 Self::none()
             }
+            Ok(string) if string != "0" && dumb_term() => Self::none(),
             Ok(string) if string != "0" => Self::unicode_nocolor(),
+            _ if dumb_term() => Self::ascii(),
             _ => Self::unicode(),
         }
     }
 }
 
+/// Whether `TERM` says the terminal probably can't draw Unicode box
+/// characters: it's `dumb`, or (outside of Windows, where it's rarely set)
+/// not set at all.
+pub(crate) fn dumb_term() -> bool {
+    match std::env::var_os("TERM") {
+        Some(term) => term == "dumb",
+        None => !cfg!(windows),
+    }
+}
+
 /**
 Styles for various parts of graphical rendering for the
 [`GraphicalReportHandler`](crate::GraphicalReportHandler).
 */
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct ThemeStyles {
     /// Style to apply to things highlighted as "error".
+    #[cfg_attr(feature = "serde", serde(with = "serde_style"))]
     pub error: Style,
     /// Style to apply to things highlighted as "warning".
+    #[cfg_attr(feature = "serde", serde(with = "serde_style"))]
     pub warning: Style,
     /// Style to apply to things highlighted as "advice".
+    #[cfg_attr(feature = "serde", serde(with = "serde_style"))]
     pub advice: Style,
     /// Style to apply to the help text.
+    #[cfg_attr(feature = "serde", serde(with = "serde_style"))]
     pub help: Style,
     /// Style to apply to filenames/links/URLs.
+    #[cfg_attr(feature = "serde", serde(with = "serde_style"))]
     pub link: Style,
     /// Style to apply to line numbers.
+    #[cfg_attr(feature = "serde", serde(with = "serde_style"))]
     pub linum: Style,
     /// Styles to cycle through (using `.iter().cycle()`), to render the lines
     /// and text for diagnostic highlights.
+    #[cfg_attr(feature = "serde", serde(with = "serde_styles"))]
     pub highlights: Vec<Style>,
 }
 
@@ -164,6 +193,7 @@ impl ThemeStyles {
 /// [`GraphicalReportHandler`](crate::GraphicalReportHandler).
 #[allow(missing_docs)]
 #[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct ThemeCharacters {
     pub hbar: char,
     pub vbar: char,
@@ -275,3 +305,242 @@ impl ThemeCharacters {
         }
     }
 }
+
+// ----------------------------------------
+// `Style` doesn't expose its colors or effects, so (de)serialization goes
+// through the SGR codes of its ANSI prefix.
+
+/// Serializable description of a [`Style`].
+#[cfg(feature = "serde")]
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct StyleDef {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    fg: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    bg: Option<String>,
+    #[serde(skip_serializing_if = "is_false")]
+    bold: bool,
+    #[serde(skip_serializing_if = "is_false")]
+    dimmed: bool,
+    #[serde(skip_serializing_if = "is_false")]
+    italic: bool,
+    #[serde(skip_serializing_if = "is_false")]
+    underline: bool,
+    #[serde(skip_serializing_if = "is_false")]
+    blink: bool,
+    #[serde(skip_serializing_if = "is_false")]
+    blink_fast: bool,
+    #[serde(skip_serializing_if = "is_false")]
+    reversed: bool,
+    #[serde(skip_serializing_if = "is_false")]
+    hidden: bool,
+    #[serde(skip_serializing_if = "is_false")]
+    strikethrough: bool,
+}
+
+#[cfg(feature = "serde")]
+fn is_false(value: &bool) -> bool {
+    !*value
+}
+
+#[cfg(feature = "serde")]
+const ANSI_COLOR_NAMES: [&str; 8] = [
+    "black", "red", "green", "yellow", "blue", "magenta", "cyan", "white",
+];
+
+#[cfg(feature = "serde")]
+impl StyleDef {
+    fn from_style(style: &Style) -> Self {
+        let prefix = style.prefix_formatter().to_string();
+        let mut codes = prefix
+            .trim_start_matches("\x1b[")
+            .trim_end_matches('m')
+            .split(';')
+            .filter_map(|code| code.parse::<u8>().ok());
+        let mut def = Self::default();
+        while let Some(code) = codes.next() {
+            match code {
+                1 => def.bold = true,
+                2 => def.dimmed = true,
+                3 => def.italic = true,
+                4 => def.underline = true,
+                5 => def.blink = true,
+                6 => def.blink_fast = true,
+                7 => def.reversed = true,
+                8 => def.hidden = true,
+                9 => def.strikethrough = true,
+                30..=39 | 90..=97 => def.fg = Self::color_name(code, &mut codes),
+                40..=49 | 100..=107 => def.bg = Self::color_name(code - 10, &mut codes),
+                _ => {}
+            }
+        }
+        def
+    }
+
+    /// Names the foreground color starting with SGR `code`, consuming the
+    /// extra codes of 256-color and RGB colors.
+    fn color_name(code: u8, codes: &mut impl Iterator<Item = u8>) -> Option<String> {
+        match code {
+            30..=37 => Some(ANSI_COLOR_NAMES[usize::from(code - 30)].to_string()),
+            90..=97 => Some(format!(
+                "bright {}",
+                ANSI_COLOR_NAMES[usize::from(code - 90)]
+            )),
+            39 => Some("default".to_string()),
+            38 => match codes.next()? {
+                5 => codes.next().map(|n| n.to_string()),
+                2 => Some(format!(
+                    "#{:02x}{:02x}{:02x}",
+                    codes.next()?,
+                    codes.next()?,
+                    codes.next()?
+                )),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    fn parse_color(name: &str) -> Result<owo_colors::DynColors, String> {
+        use owo_colors::{AnsiColors, DynColors, XtermColors};
+
+        if name == "default" {
+            return Ok(DynColors::Ansi(AnsiColors::Default));
+        }
+        if let Ok(n) = name.parse::<u8>() {
+            return Ok(DynColors::Xterm(XtermColors::from(n)));
+        }
+        name.parse()
+            .map_err(|_| format!("invalid color name: {:?}", name))
+    }
+
+    fn into_style(self) -> Result<Style, String> {
+        use owo_colors::Effect;
+
+        let mut style = Style::new();
+        if let Some(fg) = &self.fg {
+            style = style.color(Self::parse_color(fg)?);
+        }
+        if let Some(bg) = &self.bg {
+            style = style.on_color(Self::parse_color(bg)?);
+        }
+        let effects = [
+            (self.bold, Effect::Bold),
+            (self.dimmed, Effect::Dimmed),
+            (self.italic, Effect::Italic),
+            (self.underline, Effect::Underline),
+            (self.blink, Effect::Blink),
+            (self.blink_fast, Effect::BlinkFast),
+            (self.reversed, Effect::Reversed),
+            (self.hidden, Effect::Hidden),
+            (self.strikethrough, Effect::Strikethrough),
+        ];
+        for (enabled, effect) in effects {
+            if enabled {
+                style = style.effect(effect);
+            }
+        }
+        Ok(style)
+    }
+}
+
+#[cfg(feature = "serde")]
+mod serde_style {
+    use owo_colors::Style;
+    use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::StyleDef;
+
+    pub(super) fn serialize<S: Serializer>(
+        style: &Style,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        StyleDef::from_style(style).serialize(serializer)
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Style, D::Error> {
+        StyleDef::deserialize(deserializer)?
+            .into_style()
+            .map_err(D::Error::custom)
+    }
+}
+
+#[cfg(feature = "serde")]
+mod serde_styles {
+    use owo_colors::Style;
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    use super::StyleDef;
+
+    pub(super) fn serialize<S: Serializer>(
+        styles: &[Style],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(styles.iter().map(StyleDef::from_style))
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<Style>, D::Error> {
+        Vec::<StyleDef>::deserialize(deserializer)?
+            .into_iter()
+            .map(|def| def.into_style().map_err(D::Error::custom))
+            .collect()
+    }
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_serialize_theme() {
+    use serde_json::json;
+
+    let theme = GraphicalTheme {
+        characters: ThemeCharacters::ascii(),
+        styles: ThemeStyles::ansi(),
+    };
+    let value = serde_json::to_value(&theme).unwrap();
+    assert_eq!(
+        value["styles"]["link"],
+        json!({ "fg": "cyan", "bold": true, "underline": true })
+    );
+    assert_eq!(value["styles"]["error"], json!({ "fg": "red" }));
+    assert_eq!(value["characters"]["hbar"], json!("-"));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_theme_round_trip() {
+    for theme in [
+        GraphicalTheme::unicode(),
+        GraphicalTheme::none(),
+        GraphicalTheme {
+            characters: ThemeCharacters::emoji(),
+            styles: ThemeStyles::rgb(),
+        },
+    ] {
+        let json = serde_json::to_string(&theme).unwrap();
+        let loaded: GraphicalTheme = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.characters, theme.characters);
+        assert_eq!(
+            format!("{:?}", loaded.styles),
+            format!("{:?}", theme.styles)
+        );
+    }
+
+    let style = StyleDef {
+        fg: Some("208".into()),
+        bg: Some("bright blue".into()),
+        italic: true,
+        ..StyleDef::default()
+    }
+    .into_style()
+    .unwrap();
+    let json = serde_json::to_value(StyleDef::from_style(&style)).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!({ "fg": "208", "bg": "bright blue", "italic": true })
+    );
+}
@@ -96,12 +96,12 @@ impl SyntectHighlighter {
             }
         }
         // finally, attempt to guess syntax based on first line
-        return self.syntax_set.find_syntax_by_first_line(
+        self.syntax_set.find_syntax_by_first_line(
             std::str::from_utf8(contents.data())
                 .ok()?
                 .split('\n')
                 .next()?,
-        );
+        )
     }
 }
 
@@ -125,7 +125,7 @@ impl<'h> HighlighterState for SyntectHighlighterState<'h> {
                 line,
                 &self.highlighter,
             )
-            .map(|(style, str)| (convert_style(style, use_bg_color).style(str)))
+            .map(|(style, str)| convert_style(style, use_bg_color).style(str))
             .collect()
         } else {
             vec![Style::default().style(line)]
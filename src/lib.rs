@@ -1,6 +1,7 @@
 #![deny(missing_docs, missing_debug_implementations, nonstandard_style)]
 #![warn(unreachable_pub, rust_2018_idioms)]
 #![allow(unexpected_cfgs)]
+#![cfg_attr(feature = "nightly-backtrace", feature(error_generic_member_access))]
 //! You run miette? You run her code like the software? Oh. Oh! Error code for
 //! coder! Error code for One Thousand Lines!
 //!
@@ -775,22 +776,35 @@
 #[cfg(feature = "derive")]
 pub use miette_derive::*;
 
+#[cfg(feature = "anyhow")]
+pub use anyhow_diagnostic::*;
+pub use diff_source::*;
 pub use error::*;
 pub use eyreish::*;
+pub use fn_diagnostic::*;
+pub use framed_source::*;
 #[cfg(feature = "fancy-base")]
 pub use handler::*;
 pub use handlers::*;
 pub use miette_diagnostic::*;
 pub use named_source::*;
+pub use owned_diagnostic::*;
 #[cfg(feature = "fancy")]
 pub use panic::*;
 pub use protocol::*;
+#[cfg(feature = "tracing")]
+pub use tracing_event::*;
 
+#[cfg(feature = "anyhow")]
+mod anyhow_diagnostic;
 mod chain;
 mod diagnostic_chain;
 mod diagnostic_impls;
+mod diff_source;
 mod error;
 mod eyreish;
+mod fn_diagnostic;
+mod framed_source;
 #[cfg(feature = "fancy-base")]
 mod handler;
 mod handlers;
@@ -800,7 +814,10 @@ pub mod highlighters;
 pub mod macro_helpers;
 mod miette_diagnostic;
 mod named_source;
+mod owned_diagnostic;
 #[cfg(feature = "fancy")]
 mod panic;
 mod protocol;
 mod source_impls;
+#[cfg(feature = "tracing")]
+mod tracing_event;
@@ -8,6 +8,7 @@ pub struct NamedSource<S: SourceCode + 'static> {
     source: S,
     name: String,
     language: Option<String>,
+    tab_width: Option<usize>,
 }
 
 impl<S: SourceCode> std::fmt::Debug for NamedSource<S> {
@@ -15,7 +16,8 @@ impl<S: SourceCode> std::fmt::Debug for NamedSource<S> {
         f.debug_struct("NamedSource")
             .field("name", &self.name)
             .field("source", &"<redacted>")
-            .field("language", &self.language);
+            .field("language", &self.language)
+            .field("tab_width", &self.tab_width);
         Ok(())
     }
 }
@@ -31,6 +33,7 @@ impl<S: SourceCode + 'static> NamedSource<S> {
             source,
             name: name.as_ref().to_string(),
             language: None,
+            tab_width: None,
         }
     }
 
@@ -45,11 +48,38 @@ impl<S: SourceCode + 'static> NamedSource<S> {
         &self.source
     }
 
+    /// Replaces the name of this `NamedSource`, keeping its source as-is.
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
     /// Sets the [`language`](SpanContents::language) for this source code.
     pub fn with_language(mut self, language: impl Into<String>) -> Self {
         self.language = Some(language.into());
         self
     }
+
+    /// Sets the [`tab_width`](SpanContents::tab_width) for this source code,
+    /// for handlers that haven't been configured with their own.
+    pub fn with_tab_width(mut self, tab_width: usize) -> Self {
+        self.tab_width = Some(tab_width);
+        self
+    }
+
+    /// Transforms the inner [`SourceCode`] with `f`, keeping this source's
+    /// name and language.
+    pub fn map_source<U>(self, f: impl FnOnce(S) -> U) -> NamedSource<U>
+    where
+        U: SourceCode + 'static,
+    {
+        NamedSource {
+            source: f(self.source),
+            name: self.name,
+            language: self.language,
+            tab_width: self.tab_width,
+        }
+    }
 }
 
 impl<S: SourceCode + 'static> SourceCode for NamedSource<S> {
@@ -73,6 +103,32 @@ impl<S: SourceCode + 'static> SourceCode for NamedSource<S> {
         if let Some(language) = &self.language {
             contents = contents.with_language(language);
         }
+        if let Some(tab_width) = self.tab_width {
+            contents = contents.with_tab_width(tab_width);
+        }
         Ok(Box::new(contents))
     }
+
+    fn as_diff(&self) -> Option<&crate::DiffSource> {
+        self.source.as_diff()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn map_source() -> Result<(), MietteError> {
+        let source = NamedSource::new("bad_file.rs", String::from("source\ntext"))
+            .with_language("Rust")
+            .map_source(|src| src.to_uppercase().into_bytes());
+        assert_eq!(source.name(), "bad_file.rs");
+        assert_eq!(source.inner(), b"SOURCE\nTEXT");
+        let contents = source.read_span(&(7, 4).into(), 0, 0)?;
+        assert_eq!(contents.name(), Some("bad_file.rs"));
+        assert_eq!(contents.language(), Some("Rust"));
+        assert_eq!(contents.data(), b"TEXT");
+        Ok(())
+    }
 }
@@ -0,0 +1,278 @@
+use std::{
+    error::Error,
+    fmt::{Debug, Display},
+};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    Diagnostic, LabeledSpan, MietteError, MietteSpanContents, Severity, SourceCode, SourceSpan,
+    SpanContents,
+};
+
+/// Owned snapshot of a [`Diagnostic`], including its source code and related
+/// diagnostics.
+///
+/// Unlike a live `Diagnostic`, an `OwnedDiagnostic` doesn't borrow from
+/// anything, so it can be stored, sent across threads or (with the `serde`
+/// feature) serialized and sent across a process boundary, and then be
+/// rendered by any [`ReportHandler`](crate::ReportHandler) on the other side.
+///
+/// Cause chains aren't captured.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct OwnedDiagnostic {
+    /// Displayed diagnostic message
+    pub message: String,
+    /// Unique diagnostic code, see [`Diagnostic::code`]
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub code: Option<String>,
+    /// [`Diagnostic`] severity, see [`Diagnostic::severity`]
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            default,
+            skip_serializing_if = "Option::is_none",
+            deserialize_with = "deserialize_severity"
+        )
+    )]
+    pub severity: Option<Severity>,
+    /// Additional help text related to this Diagnostic
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub help: Option<String>,
+    /// URL to visit for a more detailed explanation/help about this
+    /// [`Diagnostic`].
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub url: Option<String>,
+    /// Labels to apply to this `Diagnostic`'s source code
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub labels: Vec<LabeledSpan>,
+    /// The source code the labels point into. Related diagnostics without
+    /// their own source code use their parent's, as usual.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub source_code: Option<OwnedSource>,
+    /// Snapshots of this diagnostic's related diagnostics
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub related: Vec<OwnedDiagnostic>,
+}
+
+/// Reads a severity by name, ignoring case, so that both `Severity`'s own
+/// serialization and [`JSONReportHandler`](crate::JSONReportHandler)'s
+/// lowercase names are accepted.
+#[cfg(feature = "serde")]
+fn deserialize_severity<'de, D>(deserializer: D) -> Result<Option<Severity>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Option::<String>::deserialize(deserializer)?
+        .map(|name| name.parse().map_err(serde::de::Error::custom))
+        .transpose()
+}
+
+impl OwnedDiagnostic {
+    /// Create a new `OwnedDiagnostic` with the given message and nothing else.
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            code: None,
+            severity: None,
+            help: None,
+            url: None,
+            labels: Vec::new(),
+            source_code: None,
+            related: Vec::new(),
+        }
+    }
+}
+
+impl From<&dyn Diagnostic> for OwnedDiagnostic {
+    fn from(diagnostic: &dyn Diagnostic) -> Self {
+        Self {
+            message: diagnostic.to_string(),
+            code: diagnostic.code().map(|code| code.to_string()),
+            severity: diagnostic.severity(),
+            help: diagnostic.help().map(|help| help.to_string()),
+            url: diagnostic.url().map(|url| url.to_string()),
+            labels: diagnostic
+                .labels()
+                .map(|labels| labels.collect())
+                .unwrap_or_default(),
+            source_code: diagnostic
+                .source_code_owned()
+                .and_then(|src| OwnedSource::capture(&*src)),
+            related: diagnostic
+                .related()
+                .map(|related| related.map(OwnedDiagnostic::from).collect())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+impl Display for OwnedDiagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", &self.message)
+    }
+}
+
+impl Error for OwnedDiagnostic {}
+
+impl Diagnostic for OwnedDiagnostic {
+    fn code<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        self.code
+            .as_ref()
+            .map(Box::new)
+            .map(|c| c as Box<dyn Display>)
+    }
+
+    fn severity(&self) -> Option<Severity> {
+        self.severity
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        self.help
+            .as_ref()
+            .map(Box::new)
+            .map(|c| c as Box<dyn Display>)
+    }
+
+    fn url<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        self.url
+            .as_ref()
+            .map(Box::new)
+            .map(|c| c as Box<dyn Display>)
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+        if self.labels.is_empty() {
+            return None;
+        }
+        Some(Box::new(self.labels.iter().cloned()))
+    }
+
+    fn source_code(&self) -> Option<&dyn SourceCode> {
+        self.source_code.as_ref().map(|src| src as &dyn SourceCode)
+    }
+
+    fn related<'a>(&'a self) -> Option<Box<dyn Iterator<Item = &'a dyn Diagnostic> + 'a>> {
+        if self.related.is_empty() {
+            return None;
+        }
+        Some(Box::new(
+            self.related.iter().map(|rel| rel as &dyn Diagnostic),
+        ))
+    }
+}
+
+/// Owned copy of a [`SourceCode`], as captured by [`OwnedDiagnostic`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct OwnedSource {
+    /// The name of the source, usually a file name.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub name: Option<String>,
+    /// The language of the source, used for syntax highlighting.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub language: Option<String>,
+    /// The full text of the source.
+    pub text: String,
+}
+
+impl OwnedSource {
+    /// Create a new, unnamed `OwnedSource`.
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            name: None,
+            language: None,
+            text: text.into(),
+        }
+    }
+
+    /// Sets the name of this source.
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Sets the language of this source.
+    pub fn with_language(mut self, language: impl Into<String>) -> Self {
+        self.language = Some(language.into());
+        self
+    }
+
+    /// Copies the full text of `source`. Returns `None` if the source can't
+    /// be read from its start, since labels would no longer line up with it.
+    fn capture(source: &dyn SourceCode) -> Option<Self> {
+        // Asking for every line of context around an empty span at the start
+        // reads the whole source.
+        let contents = source
+            .read_span(&SourceSpan::from(0), usize::MAX, usize::MAX)
+            .ok()?;
+        if contents.span().offset() != 0 {
+            return None;
+        }
+        Some(Self {
+            name: contents.name().map(String::from),
+            language: contents.language().map(String::from),
+            text: String::from_utf8_lossy(contents.data()).into_owned(),
+        })
+    }
+}
+
+impl SourceCode for OwnedSource {
+    fn read_span<'a>(
+        &'a self,
+        span: &SourceSpan,
+        context_lines_before: usize,
+        context_lines_after: usize,
+    ) -> Result<Box<dyn SpanContents<'a> + 'a>, MietteError> {
+        let inner = self
+            .text
+            .read_span(span, context_lines_before, context_lines_after)?;
+        let mut contents = match &self.name {
+            Some(name) => MietteSpanContents::new_named(
+                name.clone(),
+                inner.data(),
+                *inner.span(),
+                inner.line(),
+                inner.column(),
+                inner.line_count(),
+            ),
+            None => MietteSpanContents::new(
+                inner.data(),
+                *inner.span(),
+                inner.line(),
+                inner.column(),
+                inner.line_count(),
+            ),
+        };
+        if let Some(language) = &self.language {
+            contents = contents.with_language(language);
+        }
+        Ok(Box::new(contents))
+    }
+}
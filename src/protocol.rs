@@ -7,6 +7,7 @@ use std::{
     fmt::{self, Display},
     fs,
     panic::Location,
+    str::FromStr,
 };
 
 #[cfg(feature = "serde")]
@@ -36,12 +37,38 @@ pub trait Diagnostic: std::error::Error {
         None
     }
 
+    /// Whether this `Diagnostic` should stop whatever produced it, e.g. to
+    /// decide whether a build failed after collecting a bunch of
+    /// diagnostics. Renderers don't use this.
+    ///
+    /// Defaults to `true` if the [`Diagnostic::severity`] is (or is treated
+    /// as) [`Severity::Error`].
+    fn is_fatal(&self) -> bool {
+        matches!(self.severity(), None | Some(Severity::Error))
+    }
+
     /// Additional help text related to this `Diagnostic`. Do you have any
     /// advice for the poor soul who's just run into this issue?
     fn help<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
         None
     }
 
+    /// Short, concise title for this `Diagnostic`, for places where the full
+    /// message is too verbose, like lists of diagnostics.
+    ///
+    /// If `None`, reporters should use the diagnostic's [`Display`] message
+    /// instead.
+    fn title<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        None
+    }
+
+    /// Detailed description of this `Diagnostic`, to go along with its
+    /// [`Display`] message, which should then be kept short. Reporters render
+    /// it below the message.
+    fn long_message<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        None
+    }
+
     /// URL to visit for a more detailed explanation/help about this
     /// `Diagnostic`.
     fn url<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
@@ -53,11 +80,32 @@ pub trait Diagnostic: std::error::Error {
         None
     }
 
+    /// Owned variant of [`Diagnostic::source_code`], for diagnostics that
+    /// generate their source code on demand (for example by decompressing
+    /// it) instead of storing it.
+    ///
+    /// Handlers use [`Diagnostic::source_code`] when it returns something,
+    /// and only fall back to this method otherwise. The default
+    /// implementation just boxes up [`Diagnostic::source_code`].
+    fn source_code_owned(&self) -> Option<Box<dyn SourceCode + '_>> {
+        self.source_code()
+            .map(|src| Box::new(src) as Box<dyn SourceCode + '_>)
+    }
+
     /// Labels to apply to this `Diagnostic`'s [`Diagnostic::source_code`]
     fn labels(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
         None
     }
 
+    /// Unlabeled snippets of this `Diagnostic`'s [`Diagnostic::source_code`]
+    /// to show for reference, e.g. the declaration of something a label
+    /// points at. Each span is rendered with its surrounding context lines
+    /// but without an underline, and its label (if any) is used as the
+    /// snippet's caption.
+    fn context_snippets(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+        None
+    }
+
     /// Additional related `Diagnostic`s.
     fn related<'a>(&'a self) -> Option<Box<dyn Iterator<Item = &'a dyn Diagnostic> + 'a>> {
         None
@@ -208,6 +256,53 @@ pub enum Severity {
     Error,
 }
 
+impl Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Severity::Advice => "advice",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        })
+    }
+}
+
+impl FromStr for Severity {
+    type Err = ParseSeverityError;
+
+    /// Parses a [`Severity`] from its name (`error`, `warning` or `advice`),
+    /// ignoring case.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.eq_ignore_ascii_case("advice") {
+            Ok(Severity::Advice)
+        } else if s.eq_ignore_ascii_case("warning") {
+            Ok(Severity::Warning)
+        } else if s.eq_ignore_ascii_case("error") {
+            Ok(Severity::Error)
+        } else {
+            Err(ParseSeverityError(s.to_string()))
+        }
+    }
+}
+
+/// Error returned when a string can't be parsed into a [`Severity`].
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("unknown severity `{0}`, expected one of `error`, `warning` or `advice`")]
+pub struct ParseSeverityError(String);
+
+impl Diagnostic for ParseSeverityError {}
+
+#[test]
+fn test_severity_from_str() {
+    assert_eq!("Warning".parse::<Severity>(), Ok(Severity::Warning));
+    assert_eq!("ADVICE".parse::<Severity>(), Ok(Severity::Advice));
+    assert_eq!("error".parse::<Severity>(), Ok(Severity::Error));
+    assert!("fatal".parse::<Severity>().is_err());
+
+    for severity in [Severity::Advice, Severity::Warning, Severity::Error] {
+        assert_eq!(severity.to_string().parse::<Severity>(), Ok(severity));
+    }
+}
+
 #[cfg(feature = "serde")]
 #[test]
 fn test_serialize_severity() {
@@ -253,16 +348,51 @@ pub trait SourceCode: Send + Sync {
         context_lines_before: usize,
         context_lines_after: usize,
     ) -> Result<Box<dyn SpanContents<'a> + 'a>, MietteError>;
+
+    /// If this source holds two versions of a text, returns it as a
+    /// [`DiffSource`](crate::DiffSource) so handlers can render the versions
+    /// side by side. Defaults to `None`; only wrappers around other sources
+    /// need to override this.
+    fn as_diff(&self) -> Option<&crate::DiffSource> {
+        None
+    }
 }
 
 /// A labeled [`SourceSpan`].
 #[derive(Debug, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct LabeledSpan {
     #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     label: Option<String>,
     span: SourceSpan,
+    #[cfg_attr(feature = "serde", serde(default))]
     primary: bool,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "LabelStyle::is_normal")
+    )]
+    style: LabelStyle,
+}
+
+/// How a [`LabeledSpan`] should be rendered, on top of its usual underline.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum LabelStyle {
+    /// A regular label.
+    #[default]
+    Normal,
+    /// The labeled source text should be removed, as in a fix-it. Graphical
+    /// reports strike it through.
+    Deletion,
+}
+
+impl LabelStyle {
+    #[cfg(feature = "serde")]
+    fn is_normal(&self) -> bool {
+        *self == LabelStyle::Normal
+    }
 }
 
 impl LabeledSpan {
@@ -272,6 +402,7 @@ impl LabeledSpan {
             label,
             span: SourceSpan::new(SourceOffset(offset), len),
             primary: false,
+            style: LabelStyle::Normal,
         }
     }
 
@@ -281,6 +412,7 @@ impl LabeledSpan {
             label,
             span: span.into(),
             primary: false,
+            style: LabelStyle::Normal,
         }
     }
 
@@ -290,6 +422,7 @@ impl LabeledSpan {
             label,
             span: span.into(),
             primary: true,
+            style: LabelStyle::Normal,
         }
     }
 
@@ -346,6 +479,29 @@ impl LabeledSpan {
         Self::new_with_span(None, span)
     }
 
+    /// Makes a new [`LabelStyle::Deletion`] label, marking a span that should
+    /// be removed. It's labeled "remove this", which
+    /// [`set_label`](Self::set_label) can change.
+    ///
+    /// # Examples
+    /// ```
+    /// use miette::{LabelStyle, LabeledSpan};
+    ///
+    /// let source = "let x = 1;;";
+    /// let label = LabeledSpan::deletion(10..11);
+    /// assert_eq!(label.label(), Some("remove this"));
+    /// assert_eq!(label.label_style(), LabelStyle::Deletion);
+    /// ```
+    pub fn deletion(span: impl Into<SourceSpan>) -> Self {
+        Self::at(span, "remove this").with_label_style(LabelStyle::Deletion)
+    }
+
+    /// Sets the [`LabelStyle`] of this label.
+    pub fn with_label_style(mut self, style: LabelStyle) -> Self {
+        self.style = style;
+        self
+    }
+
     /// Gets the (optional) label string for this `LabeledSpan`.
     pub fn label(&self) -> Option<&str> {
         self.label.as_deref()
@@ -375,6 +531,173 @@ impl LabeledSpan {
     pub const fn primary(&self) -> bool {
         self.primary
     }
+
+    /// Returns the [`LabelStyle`] of this label.
+    pub const fn label_style(&self) -> LabelStyle {
+        self.style
+    }
+
+    /// Merges this label with `other` into a single label spanning both, if
+    /// they're adjacent or overlapping and have the same text and
+    /// [`LabelStyle`]. The merged label is primary if either of them is.
+    /// Returns `None` if the labels can't be merged.
+    ///
+    /// # Examples
+    /// ```
+    /// use miette::LabeledSpan;
+    ///
+    /// let left = LabeledSpan::at(0..3, "token");
+    /// let right = LabeledSpan::at(3..5, "token");
+    /// assert_eq!(left.merge(&right), Some(LabeledSpan::at(0..5, "token")));
+    /// ```
+    pub fn merge(&self, other: &LabeledSpan) -> Option<LabeledSpan> {
+        let start = self.offset().max(other.offset());
+        let end = (self.offset() + self.len()).min(other.offset() + other.len());
+        if start > end || self.label != other.label || self.style != other.style {
+            return None;
+        }
+        let offset = self.offset().min(other.offset());
+        let end = (self.offset() + self.len()).max(other.offset() + other.len());
+        Some(LabeledSpan {
+            label: self.label.clone(),
+            span: (offset, end - offset).into(),
+            primary: self.primary || other.primary,
+            style: self.style,
+        })
+    }
+}
+
+/// Convenience for hand-written [`Diagnostic::labels`] implementations that
+/// only ever have a single label.
+///
+/// # Examples
+/// ```
+/// use miette::{Diagnostic, LabeledSpan, SourceSpan};
+///
+/// #[derive(Debug, thiserror::Error)]
+/// #[error("bad thing")]
+/// struct BadThing {
+///     span: SourceSpan,
+/// }
+///
+/// impl Diagnostic for BadThing {
+///     fn labels(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+///         Some(miette::single_label(self.span, "this is bad"))
+///     }
+/// }
+/// ```
+pub fn single_label<'a>(
+    span: impl Into<SourceSpan>,
+    label: impl Into<String>,
+) -> Box<dyn Iterator<Item = LabeledSpan> + 'a> {
+    Box::new(std::iter::once(LabeledSpan::at(span, label)))
+}
+
+/// Collects the text, offset and length of each of `diagnostic`'s labels, in
+/// order. Useful for testing that a diagnostic points at the right places
+/// without matching against rendered output.
+///
+/// # Examples
+/// ```
+/// use miette::{extract_labels, LabeledSpan, MietteDiagnostic};
+///
+/// let diag = MietteDiagnostic::new("oops").with_labels([
+///     LabeledSpan::at(0..3, "here"),
+///     LabeledSpan::underline(5..7),
+/// ]);
+/// assert_eq!(
+///     extract_labels(&diag),
+///     vec![(Some("here".to_string()), 0, 3), (None, 5, 2)]
+/// );
+/// ```
+pub fn extract_labels(diagnostic: &dyn Diagnostic) -> Vec<(Option<String>, usize, usize)> {
+    diagnostic
+        .labels()
+        .map(|labels| {
+            labels
+                .map(|label| (label.label().map(String::from), label.offset(), label.len()))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Collects every [`SourceCode`] referenced by `diagnostic`, its
+/// [`diagnostic_source`](Diagnostic::diagnostic_source) chain and its
+/// [`related`](Diagnostic::related) diagnostics, depth first, along with the
+/// name each source reports for its contents, if any. Useful for tooling that
+/// needs to know which files a diagnostic points into.
+///
+/// # Examples
+/// ```
+/// use miette::{collect_sources, MietteDiagnostic, NamedSource, Report};
+///
+/// let report = Report::new(MietteDiagnostic::new("oops"))
+///     .with_source_code(NamedSource::new("main.rs", "fn main() {}"));
+/// let sources = collect_sources(&*report);
+/// assert_eq!(sources.len(), 1);
+/// assert_eq!(sources[0].1.as_deref(), Some("main.rs"));
+/// ```
+pub fn collect_sources(diagnostic: &dyn Diagnostic) -> Vec<(&dyn SourceCode, Option<String>)> {
+    fn walk<'a>(
+        diagnostic: &'a dyn Diagnostic,
+        out: &mut Vec<(&'a dyn SourceCode, Option<String>)>,
+    ) {
+        if let Some(source) = diagnostic.source_code() {
+            let name = source
+                .read_span(&SourceSpan::from(0..0), 0, 0)
+                .ok()
+                .and_then(|contents| contents.name().map(String::from));
+            out.push((source, name));
+        }
+        if let Some(inner) = diagnostic.diagnostic_source() {
+            walk(inner, out);
+        }
+        for related in diagnostic.related().into_iter().flatten() {
+            walk(related, out);
+        }
+    }
+
+    let mut out = Vec::new();
+    walk(diagnostic, &mut out);
+    out
+}
+
+/// Reference to a numbered label, displayed as `[n]`, for diagnostic messages
+/// that point at labels numbered by
+/// [`GraphicalReportHandler::with_numbered_labels`](crate::GraphicalReportHandler::with_numbered_labels).
+///
+/// Labels are numbered from 1, in the order [`Diagnostic::labels`] returns
+/// them.
+///
+/// # Examples
+/// ```
+/// use miette::LabelRef;
+///
+/// let message = format!("{} is redefined at {}", LabelRef(1), LabelRef(2));
+/// assert_eq!(message, "[1] is redefined at [2]");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LabelRef(pub usize);
+
+impl Display for LabelRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}]", self.0)
+    }
+}
+
+#[test]
+fn test_merge_labeled_spans() {
+    let left = LabeledSpan::at(0..3, "token");
+    assert_eq!(
+        left.merge(&LabeledSpan::at(3..5, "token")),
+        Some(LabeledSpan::at(0..5, "token"))
+    );
+    assert_eq!(
+        LabeledSpan::at(2..5, "token").merge(&left),
+        Some(LabeledSpan::at(0..5, "token"))
+    );
+    assert_eq!(left.merge(&LabeledSpan::at(3..5, "other")), None);
+    assert_eq!(left.merge(&LabeledSpan::at(4..5, "token")), None);
 }
 
 #[cfg(feature = "serde")]
@@ -460,6 +783,21 @@ pub trait SpanContents<'a> {
     fn language(&self) -> Option<&str> {
         None
     }
+
+    /// Optional method. The width of a tab character in this source code, in
+    /// columns, if it's known. Handlers use this when they haven't been
+    /// configured with a tab width of their own.
+    fn tab_width(&self) -> Option<usize> {
+        None
+    }
+
+    /// The data inside the associated span, as text. Returns a
+    /// [`MietteError::IoError`] with [`std::io::ErrorKind::InvalidData`] if
+    /// the data isn't valid UTF-8.
+    fn text(&self) -> Result<&'a str, MietteError> {
+        std::str::from_utf8(self.data())
+            .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err).into())
+    }
 }
 
 /**
@@ -481,6 +819,8 @@ pub struct MietteSpanContents<'a> {
     name: Option<String>,
     // Optional language
     language: Option<String>,
+    // Optional tab width
+    tab_width: Option<usize>,
 }
 
 impl<'a> MietteSpanContents<'a> {
@@ -500,6 +840,7 @@ impl<'a> MietteSpanContents<'a> {
             line_count,
             name: None,
             language: None,
+            tab_width: None,
         }
     }
 
@@ -520,14 +861,34 @@ impl<'a> MietteSpanContents<'a> {
             line_count,
             name: Some(name),
             language: None,
+            tab_width: None,
         }
     }
 
+    /// Sets the [`name`](SpanContents::name) of the 'file' these contents
+    /// came from.
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.set_name(name);
+        self
+    }
+
+    /// Sets the [`name`](SpanContents::name) of the 'file' these contents
+    /// came from, in place.
+    pub fn set_name(&mut self, name: impl Into<String>) {
+        self.name = Some(name.into());
+    }
+
     /// Sets the [`language`](SpanContents::language) for syntax highlighting.
     pub fn with_language(mut self, language: impl Into<String>) -> Self {
         self.language = Some(language.into());
         self
     }
+
+    /// Sets the [`tab_width`](SpanContents::tab_width) used for rendering.
+    pub fn with_tab_width(mut self, tab_width: usize) -> Self {
+        self.tab_width = Some(tab_width);
+        self
+    }
 }
 
 impl<'a> SpanContents<'a> for MietteSpanContents<'a> {
@@ -552,6 +913,19 @@ impl<'a> SpanContents<'a> for MietteSpanContents<'a> {
     fn language(&self) -> Option<&str> {
         self.language.as_deref()
     }
+    fn tab_width(&self) -> Option<usize> {
+        self.tab_width
+    }
+}
+
+#[test]
+fn test_span_contents_with_name() {
+    let contents = MietteSpanContents::new(b"text", (0, 4).into(), 0, 0, 1);
+    assert_eq!(contents.name(), None);
+    let mut contents = contents.with_name("foo.rs");
+    assert_eq!(contents.name(), Some("foo.rs"));
+    contents.set_name(String::from("bar.rs"));
+    assert_eq!(contents.name(), Some("bar.rs"));
 }
 
 /// Span within a [`SourceCode`]
@@ -588,6 +962,46 @@ impl SourceSpan {
     pub const fn is_empty(&self) -> bool {
         self.length == 0
     }
+
+    /// Splits this span in two at the absolute byte `offset`, returning the
+    /// part before it and the part from it onwards. Splitting at either end
+    /// gives an empty half. Returns `None` if `offset` is outside the span.
+    pub fn split_at(&self, offset: ByteOffset) -> Option<(SourceSpan, SourceSpan)> {
+        let start = self.offset();
+        if offset < start || offset - start > self.length {
+            return None;
+        }
+        Some((
+            (start, offset - start).into(),
+            (offset, self.length - (offset - start)).into(),
+        ))
+    }
+
+    /// Clamps this span so it fits within a source of `source_len` bytes. The
+    /// offset is capped at `source_len`, and the length is shortened so the
+    /// span ends no later than `source_len`.
+    pub fn clamp_to(&self, source_len: usize) -> SourceSpan {
+        let offset = self.offset().min(source_len);
+        let length = self.length.min(source_len - offset);
+        (offset, length).into()
+    }
+}
+
+/// Generates spans whose end doesn't overflow a `usize`.
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for SourceSpan {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        let offset = SourceOffset::arbitrary(u)?;
+        let length = u.int_in_range(0..=usize::MAX - offset.offset())?;
+        Ok(Self { offset, length })
+    }
+
+    fn size_hint(depth: usize) -> (usize, Option<usize>) {
+        arbitrary::size_hint::and(
+            SourceOffset::size_hint(depth),
+            <usize as arbitrary::Arbitrary>::size_hint(depth),
+        )
+    }
 }
 
 impl From<(ByteOffset, usize)> for SourceSpan {
@@ -681,6 +1095,7 @@ Newtype that represents the [`ByteOffset`] from the beginning of a [`SourceCode`
 */
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct SourceOffset(ByteOffset);
 
 impl SourceOffset {
@@ -698,15 +1113,21 @@ impl SourceOffset {
         let mut line = 0usize;
         let mut col = 0usize;
         let mut offset = 0usize;
-        for char in source.as_ref().chars() {
+        let mut chars = source.as_ref().chars().peekable();
+        while let Some(char) = chars.next() {
             if line + 1 >= loc_line && col + 1 >= loc_col {
                 break;
             }
-            if char == '\n' {
-                col = 0;
-                line += 1;
-            } else {
-                col += 1;
+            match char {
+                '\r' if chars.peek() == Some(&'\n') => {
+                    // Let the `\n` end the line.
+                    col += 1;
+                }
+                '\r' | '\n' => {
+                    col = 0;
+                    line += 1;
+                }
+                _ => col += 1,
             }
             offset += char.len_utf8();
         }
@@ -765,6 +1186,18 @@ fn test_source_offset_from_location() {
     );
 }
 
+#[test]
+fn test_source_offset_from_location_cr_only() {
+    let source = "f\r\roo\rbar";
+
+    assert_eq!(SourceOffset::from_location(source, 1, 1).offset(), 0);
+    assert_eq!(SourceOffset::from_location(source, 2, 1).offset(), 2);
+    assert_eq!(SourceOffset::from_location(source, 3, 1).offset(), 3);
+    assert_eq!(SourceOffset::from_location(source, 3, 3).offset(), 5);
+    assert_eq!(SourceOffset::from_location(source, 4, 1).offset(), 6);
+    assert_eq!(SourceOffset::from_location(source, 4, 3).offset(), 8);
+}
+
 #[cfg(feature = "serde")]
 #[test]
 fn test_serialize_source_offset() {
@@ -779,3 +1212,55 @@ fn test_deserialize_source_offset() {
     let offset: SourceOffset = serde_json::from_str("0").unwrap();
     assert_eq!(offset, SourceOffset::from(0));
 }
+
+#[test]
+fn test_span_contents_text() {
+    let contents = MietteSpanContents::new(b"foo\nbar", (0, 7).into(), 0, 0, 2);
+    assert_eq!(contents.text().unwrap(), "foo\nbar");
+
+    let contents = MietteSpanContents::new(b"foo\xff", (0, 4).into(), 0, 0, 1);
+    match contents.text() {
+        Err(MietteError::IoError(err)) => {
+            assert_eq!(err.kind(), std::io::ErrorKind::InvalidData)
+        }
+        other => panic!("expected an invalid data error, got {:?}", other),
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+#[test]
+fn test_arbitrary_spans() {
+    use arbitrary::{Arbitrary, Unstructured};
+
+    let bytes: Vec<u8> = (0..=255u8).cycle().take(4096).collect();
+    let mut u = Unstructured::new(&bytes);
+    while !u.is_empty() {
+        let span = SourceSpan::arbitrary(&mut u).unwrap();
+        assert!(span.offset().checked_add(span.len()).is_some());
+        let label = LabeledSpan::arbitrary(&mut u).unwrap();
+        assert!(label.offset().checked_add(label.len()).is_some());
+    }
+
+    // Even an all-ones input can't push the end of a span past `usize::MAX`.
+    let bytes = [0xff; 64];
+    let span = SourceSpan::arbitrary(&mut Unstructured::new(&bytes)).unwrap();
+    assert!(span.offset().checked_add(span.len()).is_some());
+}
+
+#[test]
+fn test_span_split_at() {
+    let span = SourceSpan::from((10, 4));
+    assert_eq!(span.split_at(12), Some(((10, 2).into(), (12, 2).into())));
+    assert_eq!(span.split_at(10), Some(((10, 0).into(), (10, 4).into())));
+    assert_eq!(span.split_at(14), Some(((10, 4).into(), (14, 0).into())));
+    assert_eq!(span.split_at(9), None);
+    assert_eq!(span.split_at(15), None);
+}
+
+#[test]
+fn test_span_clamp_to() {
+    let span: SourceSpan = (10, 20).into();
+    assert_eq!(span.clamp_to(100), span);
+    assert_eq!(span.clamp_to(15), (10, 5).into());
+    assert_eq!(span.clamp_to(5), (5, 0).into());
+}
@@ -104,7 +104,7 @@ impl SourceCode for [u8] {
     }
 }
 
-impl<'src> SourceCode for &'src [u8] {
+impl SourceCode for &[u8] {
     fn read_span<'a>(
         &'a self,
         span: &SourceSpan,
@@ -143,7 +143,7 @@ impl SourceCode for str {
 }
 
 /// Makes `src: &'static str` or `struct S<'a> { src: &'a str }` usable.
-impl<'s> SourceCode for &'s str {
+impl SourceCode for &str {
     fn read_span<'a>(
         &'a self,
         span: &SourceSpan,
@@ -154,6 +154,23 @@ impl<'s> SourceCode for &'s str {
     }
 }
 
+/// Lets [`Diagnostic::source_code_owned`](crate::Diagnostic::source_code_owned)
+/// box up a borrowed [`Diagnostic::source_code`](crate::Diagnostic::source_code).
+impl<'s> SourceCode for &'s (dyn SourceCode + 's) {
+    fn read_span<'a>(
+        &'a self,
+        span: &SourceSpan,
+        context_lines_before: usize,
+        context_lines_after: usize,
+    ) -> Result<Box<dyn SpanContents<'a> + 'a>, MietteError> {
+        (**self).read_span(span, context_lines_before, context_lines_after)
+    }
+
+    fn as_diff(&self) -> Option<&crate::DiffSource> {
+        (**self).as_diff()
+    }
+}
+
 impl SourceCode for String {
     fn read_span<'a>(
         &'a self,
@@ -175,8 +192,15 @@ impl<T: ?Sized + SourceCode> SourceCode for Arc<T> {
         self.as_ref()
             .read_span(span, context_lines_before, context_lines_after)
     }
+
+    fn as_diff(&self) -> Option<&crate::DiffSource> {
+        self.as_ref().as_diff()
+    }
 }
 
+/// Makes `Cow<'_, str>` usable, whether borrowed or owned. Note that sources
+/// attached to a [`Report`](crate::Report) must be `'static`, so use
+/// `Cow<'static, str>` there.
 impl<T: ?Sized + SourceCode + ToOwned> SourceCode for Cow<'_, T>
 where
     // The minimal bounds are used here.
@@ -195,6 +219,10 @@ where
         self.as_ref()
             .read_span(span, context_lines_before, context_lines_after)
     }
+
+    fn as_diff(&self) -> Option<&crate::DiffSource> {
+        self.as_ref().as_diff()
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,43 @@
+use crate::{Diagnostic, Severity};
+
+/// Emits `diag` as a [`tracing`] event, so diagnostics can flow into the same
+/// structured logs as everything else.
+///
+/// The event's level follows the diagnostic's [`Severity`]: errors (and
+/// diagnostics without a severity) are logged at `ERROR`, warnings at `WARN`
+/// and advice at `INFO`. The message is the diagnostic's `Display` output,
+/// and the event carries `code` and `url` fields (when present) plus a
+/// `labels` field with the number of labels.
+///
+/// ```rust
+/// use miette::{emit_tracing, MietteDiagnostic, Severity};
+///
+/// let diag = MietteDiagnostic::new("deprecated option")
+///     .with_code("config::deprecated")
+///     .with_severity(Severity::Warning);
+/// emit_tracing(&diag);
+/// ```
+pub fn emit_tracing(diag: &dyn Diagnostic) {
+    let code = diag.code().map(|code| code.to_string());
+    let url = diag.url().map(|url| url.to_string());
+    let labels = diag.labels().map_or(0, |labels| labels.count());
+
+    macro_rules! emit {
+        ($level:expr) => {
+            tracing::event!(
+                $level,
+                code = code.as_deref(),
+                url = url.as_deref(),
+                labels,
+                "{}",
+                diag
+            )
+        };
+    }
+
+    match diag.severity() {
+        Some(Severity::Error) | None => emit!(tracing::Level::ERROR),
+        Some(Severity::Warning) => emit!(tracing::Level::WARN),
+        Some(Severity::Advice) => emit!(tracing::Level::INFO),
+    }
+}
@@ -160,3 +160,33 @@ fn color_always_rgb_always() {
         Rgb,
     );
 }
+
+#[test]
+fn dumb_term_uses_ascii() {
+    let lock = COLOR_ENV_VARS.lock().unwrap();
+    let guard = EnvVarGuard::new("TERM");
+
+    std::env::set_var("TERM", "dumb");
+    let handler = MietteHandlerOpts::new().force_graphical(true).build();
+    let out = format!("{:?}", FormatTester(handler));
+    assert!(out.contains("x oops!"));
+    assert!(!out.contains('×'));
+
+    drop(guard);
+    drop(lock);
+}
+
+#[test]
+fn colored_report_ignores_no_color() {
+    let lock = COLOR_ENV_VARS.lock().unwrap();
+    let guard = EnvVarGuard::new("NO_COLOR");
+
+    std::env::set_var("NO_COLOR", "1");
+    let report = miette::Report::new(MyBad);
+    let out = format!("{:?}", report.colored());
+    assert!(out.contains('\u{1b}'));
+    assert_eq!(out, format!("{}", report.colored()));
+
+    drop(guard);
+    drop(lock);
+}
@@ -1,4 +1,4 @@
-use miette::{Diagnostic, Report, Severity, SourceSpan};
+use miette::{Diagnostic, LabeledSpan, Report, Severity, SourceSpan};
 use thiserror::Error;
 
 #[test]
@@ -159,6 +159,63 @@ fn path_severity() {
     assert_eq!(Some(Severity::Warning), FooEnum::X.severity());
 }
 
+#[test]
+fn fatal() {
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("welp")]
+    #[diagnostic(severity(Warning), fatal)]
+    struct FooStruct;
+
+    assert!(FooStruct.is_fatal());
+
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("welp")]
+    enum FooEnum {
+        #[diagnostic(severity(Warning), fatal)]
+        X,
+        #[diagnostic(non_fatal)]
+        Y,
+        #[diagnostic(severity(Warning))]
+        Z,
+        W,
+    }
+
+    assert!(FooEnum::X.is_fatal());
+    assert!(!FooEnum::Y.is_fatal());
+    assert!(!FooEnum::Z.is_fatal());
+    assert!(FooEnum::W.is_fatal());
+}
+
+#[test]
+fn const_generics() {
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("expected {N} items")]
+    #[diagnostic(code(foo::bar), help("got {}", self.items.len()))]
+    struct FooStruct<const N: usize> {
+        items: Vec<u8>,
+        #[label("here")]
+        span: SourceSpan,
+    }
+
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("welp")]
+    enum FooEnum<'a, T: std::fmt::Debug, const N: usize> {
+        #[diagnostic(code(foo::x))]
+        X(&'a T),
+    }
+
+    let err = FooStruct::<3> {
+        items: vec![1],
+        span: (0, 1).into(),
+    };
+    assert_eq!(err.to_string(), "expected 3 items");
+    assert_eq!(err.code().unwrap().to_string(), "foo::bar");
+    assert_eq!(err.help().unwrap().to_string(), "got 1");
+    assert_eq!(err.labels().unwrap().count(), 1);
+
+    assert_eq!(FooEnum::<_, 2>::X(&1).code().unwrap().to_string(), "foo::x");
+}
+
 #[test]
 fn list_help() {
     #[derive(Debug, Diagnostic, Error)]
@@ -184,6 +241,71 @@ fn list_help() {
     );
 }
 
+#[test]
+fn title() {
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("the configuration file at {0} couldn't be parsed")]
+    #[diagnostic(title("bad config: {0}"))]
+    struct FooStruct<'a>(&'a str);
+
+    assert_eq!(
+        "bad config: foo.toml".to_string(),
+        FooStruct("foo.toml").title().unwrap().to_string()
+    );
+
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("welp")]
+    enum FooEnum {
+        #[diagnostic(title = "x")]
+        X,
+        Y,
+    }
+
+    assert_eq!("x".to_string(), FooEnum::X.title().unwrap().to_string());
+    assert!(FooEnum::Y.title().is_none());
+
+    #[derive(Debug, Diagnostic, Error)]
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    struct Transparent<'a>(FooStruct<'a>);
+
+    assert_eq!(
+        "bad config: foo.toml".to_string(),
+        Transparent(FooStruct("foo.toml"))
+            .title()
+            .unwrap()
+            .to_string()
+    );
+}
+
+#[test]
+fn long_message() {
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("bad config")]
+    #[diagnostic(long_message("the configuration file at {0} couldn't be parsed"))]
+    struct FooStruct<'a>(&'a str);
+
+    let err = FooStruct("foo.toml");
+    assert_eq!(
+        "the configuration file at foo.toml couldn't be parsed".to_string(),
+        err.long_message().unwrap().to_string()
+    );
+
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("welp")]
+    enum FooEnum {
+        #[diagnostic(long_message = "x")]
+        X,
+        Y,
+    }
+
+    assert_eq!(
+        "x".to_string(),
+        FooEnum::X.long_message().unwrap().to_string()
+    );
+    assert!(FooEnum::Y.long_message().is_none());
+}
+
 #[test]
 fn fmt_help() {
     #[derive(Debug, Diagnostic, Error)]
@@ -390,6 +512,34 @@ fn url_docsrs() {
     );
 }
 
+#[test]
+fn url_template() {
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("welp")]
+    #[diagnostic(code(E123), url_template = "https://errors.example.com/{code}")]
+    struct Foo;
+
+    assert_eq!(
+        "https://errors.example.com/E123",
+        Foo.url().unwrap().to_string()
+    );
+
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("welp")]
+    #[diagnostic(url_template("https://errors.example.com/{code}"))]
+    enum Bar {
+        #[diagnostic(code(E1))]
+        X,
+        Y,
+    }
+
+    assert_eq!(
+        "https://errors.example.com/E1",
+        Bar::X.url().unwrap().to_string()
+    );
+    assert!(Bar::Y.url().is_none());
+}
+
 const SNIPPET_TEXT: &str = "hello from miette";
 
 #[derive(Debug, Diagnostic, Error)]
@@ -632,3 +782,103 @@ fn test_optional_source_code() {
     .source_code()
     .is_some());
 }
+
+#[test]
+fn test_extract_labels() {
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("oops!")]
+    struct Foo {
+        #[label("first")]
+        first: SourceSpan,
+        #[label]
+        second: SourceSpan,
+    }
+
+    let err = Foo {
+        first: (1, 2).into(),
+        second: (5, 3).into(),
+    };
+    assert_eq!(
+        miette::extract_labels(&err),
+        vec![(Some("first".to_string()), 1, 2), (None, 5, 3)]
+    );
+    assert!(miette::extract_labels(&miette::MietteDiagnostic::new("no labels")).is_empty());
+}
+
+#[test]
+fn test_collect_sources() {
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("related")]
+    struct Related {
+        #[source_code]
+        src: miette::NamedSource<String>,
+    }
+
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("oops!")]
+    struct Foo {
+        #[source_code]
+        src: miette::NamedSource<String>,
+        #[related]
+        related: Vec<Related>,
+    }
+
+    let err = Foo {
+        src: miette::NamedSource::new("main.rs", "fn main() {}".to_string()),
+        related: vec![Related {
+            src: miette::NamedSource::new("lib.rs", "pub fn lib() {}".to_string()),
+        }],
+    };
+    let names: Vec<_> = miette::collect_sources(&err)
+        .into_iter()
+        .map(|(_, name)| name)
+        .collect();
+    assert_eq!(
+        names,
+        vec![Some("main.rs".to_string()), Some("lib.rs".to_string())]
+    );
+}
+
+#[test]
+fn forward_context_snippets() {
+    #[derive(Debug, Error)]
+    #[error("oops!")]
+    struct Inner;
+
+    impl Diagnostic for Inner {
+        fn context_snippets(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+            Some(Box::new(std::iter::once(LabeledSpan::at(
+                4..5,
+                "declared here",
+            ))))
+        }
+    }
+
+    #[derive(Debug, Diagnostic, Error)]
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    struct Transparent(Inner);
+
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("outer")]
+    #[diagnostic(forward(0))]
+    struct Forwarded(Inner);
+
+    #[derive(Debug, Diagnostic, Error)]
+    enum ForwardedEnum {
+        #[error(transparent)]
+        #[diagnostic(transparent)]
+        Inner(Inner),
+        #[error("other")]
+        Other,
+    }
+
+    let expected = vec![LabeledSpan::at(4..5, "declared here")];
+    let snippets = |diag: &dyn Diagnostic| -> Vec<LabeledSpan> {
+        diag.context_snippets().into_iter().flatten().collect()
+    };
+    assert_eq!(snippets(&Transparent(Inner)), expected);
+    assert_eq!(snippets(&Forwarded(Inner)), expected);
+    assert_eq!(snippets(&ForwardedEnum::Inner(Inner)), expected);
+    assert!(snippets(&ForwardedEnum::Other).is_empty());
+}
@@ -1,8 +1,9 @@
 #![cfg(feature = "fancy-no-backtrace")]
 
 use miette::{
-    Diagnostic, GraphicalReportHandler, GraphicalTheme, MietteError, NamedSource,
-    NarratableReportHandler, Report, SourceSpan,
+    Diagnostic, DiffSource, FramedSource, GraphicalReportHandler, GraphicalTheme, GutterAlign,
+    HelpPosition, LabelRef, LabeledSpan, MietteError, NamedSource, NarratableReportHandler,
+    RenderSections, Report, Section, Severity, SourceSpan,
 };
 use thiserror::Error;
 
@@ -443,6 +444,39 @@ fn empty_source() -> Result<(), MietteError> {
     Ok(())
 }
 
+#[test]
+fn collapse_empty_source() -> Result<(), MietteError> {
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("oops!")]
+    #[diagnostic(code(oops::my::bad), help("try doing it better next time?"))]
+    struct MyBad {
+        #[source_code]
+        src: NamedSource<String>,
+        #[label("this bit here")]
+        highlight: SourceSpan,
+    }
+
+    let src = "".to_string();
+    let err = MyBad {
+        src: NamedSource::new("bad_file.rs", src),
+        highlight: (0, 0).into(),
+    };
+    let out = fmt_report_with_settings(err.into(), |handler| {
+        handler.with_collapse_empty_source(true)
+    });
+    println!("Error: {}", out);
+    let expected = r#"oops::my::bad
+
+  × oops!
+  (empty source: bad_file.rs)
+  help: try doing it better next time?
+"#
+    .trim_start()
+    .to_string();
+    assert_eq!(expected, out);
+    Ok(())
+}
+
 #[test]
 fn multiple_spans_multiline() {
     #[derive(Error, Debug, Diagnostic)]
@@ -674,6 +708,214 @@ fn single_line_highlight() -> Result<(), MietteError> {
     Ok(())
 }
 
+#[test]
+fn path_base() -> Result<(), MietteError> {
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("oops!")]
+    #[diagnostic(code(oops::my::bad), help("try doing it better next time?"))]
+    struct MyBad {
+        #[source_code]
+        src: NamedSource<String>,
+        #[label("this bit here")]
+        highlight: SourceSpan,
+    }
+
+    let src = "source\n  text\n    here".to_string();
+    let err = MyBad {
+        src: NamedSource::new("/home/me/project/src/bad_file.rs", src),
+        highlight: (9, 4).into(),
+    };
+    let out = fmt_report_with_settings(err.into(), |handler| {
+        handler
+            .without_syntax_highlighting()
+            .with_path_base("/home/me/project".into())
+    });
+    println!("Error: {}", out);
+    let expected = r#"oops::my::bad
+
+  × oops!
+   ╭─[src/bad_file.rs:2:3]
+ 1 │ source
+ 2 │   text
+   ·   ──┬─
+   ·     ╰── this bit here
+ 3 │     here
+   ╰────
+  help: try doing it better next time?
+"#
+    .trim_start()
+    .to_string();
+    assert_eq!(expected, out);
+    Ok(())
+}
+
+#[test]
+fn path_base_outside() -> Result<(), MietteError> {
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("oops!")]
+    #[diagnostic(code(oops::my::bad))]
+    struct MyBad {
+        #[source_code]
+        src: NamedSource<String>,
+        #[label("this bit here")]
+        highlight: SourceSpan,
+    }
+
+    let src = "source\n  text\n    here".to_string();
+    let err = MyBad {
+        src: NamedSource::new("/elsewhere/src/main.rs", src),
+        highlight: (9, 4).into(),
+    };
+    let out = fmt_report_with_settings(err.into(), |handler| {
+        handler.with_path_base("/project".into())
+    });
+    println!("Error: {}", out);
+    assert!(out.contains("╭─[/elsewhere/src/main.rs:2:3]"));
+    Ok(())
+}
+
+#[test]
+fn generated_source_code() -> Result<(), MietteError> {
+    #[derive(Debug, Error)]
+    #[error("oops!")]
+    struct MyBad {
+        lines: Vec<&'static str>,
+        highlight: SourceSpan,
+    }
+
+    impl Diagnostic for MyBad {
+        fn source_code_owned(&self) -> Option<Box<dyn miette::SourceCode + '_>> {
+            Some(Box::new(NamedSource::new(
+                "bad_file.rs",
+                self.lines.join("\n"),
+            )))
+        }
+
+        fn labels(&self) -> Option<Box<dyn Iterator<Item = miette::LabeledSpan> + '_>> {
+            Some(miette::single_label(self.highlight, "this bit here"))
+        }
+    }
+
+    let err = MyBad {
+        lines: vec!["source", "  text", "    here"],
+        highlight: (9, 4).into(),
+    };
+    let out = fmt_report(err.into());
+    println!("Error: {}", out);
+    let expected = r#"
+  × oops!
+   ╭─[bad_file.rs:2:3]
+ 1 │ source
+ 2 │   text
+   ·   ──┬─
+   ·     ╰── this bit here
+ 3 │     here
+   ╰────
+"#
+    .to_string();
+    assert_eq!(expected, out);
+    Ok(())
+}
+
+#[test]
+fn generated_source_code_transparent() -> Result<(), MietteError> {
+    #[derive(Debug, Error)]
+    #[error("oops!")]
+    struct MyBad {
+        lines: Vec<&'static str>,
+        highlight: SourceSpan,
+    }
+
+    impl Diagnostic for MyBad {
+        fn source_code_owned(&self) -> Option<Box<dyn miette::SourceCode + '_>> {
+            Some(Box::new(NamedSource::new(
+                "bad_file.rs",
+                self.lines.join("\n"),
+            )))
+        }
+
+        fn labels(&self) -> Option<Box<dyn Iterator<Item = miette::LabeledSpan> + '_>> {
+            Some(miette::single_label(self.highlight, "this bit here"))
+        }
+    }
+
+    #[derive(Debug, Diagnostic, Error)]
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    struct Transparent(MyBad);
+
+    #[derive(Debug, Diagnostic, Error)]
+    enum Forwarded {
+        #[error(transparent)]
+        #[diagnostic(forward(0))]
+        Bad(MyBad),
+    }
+
+    let bad = || MyBad {
+        lines: vec!["source", "  text", "    here"],
+        highlight: (9, 4).into(),
+    };
+    let expected = r#"
+  × oops!
+   ╭─[bad_file.rs:2:3]
+ 1 │ source
+ 2 │   text
+   ·   ──┬─
+   ·     ╰── this bit here
+ 3 │     here
+   ╰────
+"#
+    .to_string();
+    let out = fmt_report(Transparent(bad()).into());
+    println!("Error: {}", out);
+    assert_eq!(expected, out);
+    let out = fmt_report(Forwarded::Bad(bad()).into());
+    println!("Error: {}", out);
+    assert_eq!(expected, out);
+    Ok(())
+}
+
+#[test]
+fn manual_single_label() -> Result<(), MietteError> {
+    #[derive(Debug, Error)]
+    #[error("oops!")]
+    struct MyBad {
+        src: NamedSource<String>,
+        highlight: SourceSpan,
+    }
+
+    impl Diagnostic for MyBad {
+        fn source_code(&self) -> Option<&dyn miette::SourceCode> {
+            Some(&self.src)
+        }
+
+        fn labels(&self) -> Option<Box<dyn Iterator<Item = miette::LabeledSpan> + '_>> {
+            Some(miette::single_label(self.highlight, "this bit here"))
+        }
+    }
+
+    let src = "source\n  text\n    here".to_string();
+    let err = MyBad {
+        src: NamedSource::new("bad_file.rs", src),
+        highlight: (9, 4).into(),
+    };
+    let out = fmt_report(err.into());
+    println!("Error: {}", out);
+    let expected = r#"
+  × oops!
+   ╭─[bad_file.rs:2:3]
+ 1 │ source
+ 2 │   text
+   ·   ──┬─
+   ·     ╰── this bit here
+ 3 │     here
+   ╰────
+"#
+    .to_string();
+    assert_eq!(expected, out);
+    Ok(())
+}
+
 #[test]
 fn external_source() -> Result<(), MietteError> {
     #[derive(Debug, Diagnostic, Error)]
@@ -1171,7 +1413,7 @@ fn multiline_highlight_adjacent() -> Result<(), MietteError> {
  1 │     source
  2 │ ╭─▶   text
  3 │ ├─▶     here
-   · ╰──── these two lines
+   · ╰────────┴─ these two lines
    ╰────
   help: try doing it better next time?
 "#
@@ -1207,8 +1449,8 @@ fn multiline_highlight_multiline_label() -> Result<(), MietteError> {
  1 │     source
  2 │ ╭─▶   text
  3 │ ├─▶     here
-   · ╰──┤ these two lines
-   ·    │ are the problem
+   · ╰────────┴─┤ these two lines
+   ·            │ are the problem
    ╰────
   help: try doing it better next time?
 "#
@@ -1255,7 +1497,7 @@ line5
  2 │ │╭─▶ line2
  3 │ ││   line3
  4 │ │├─▶ line4
-   · │╰──── block 2
+   · │╰───┴─ block 2
  5 │ ├──▶ line5
    · ╰───── block 1
    ╰────
@@ -1267,6 +1509,68 @@ line5
     Ok(())
 }
 
+#[test]
+fn multiline_highlight_ends_mid_line() -> Result<(), MietteError> {
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("oops!")]
+    struct MyBad {
+        #[source_code]
+        src: NamedSource<String>,
+        #[label = "ends here"]
+        highlight: SourceSpan,
+    }
+
+    let src = "first line\nsecond line".to_string();
+    let err = MyBad {
+        src: NamedSource::new("bad_file.rs", src),
+        highlight: (6, 10).into(),
+    };
+    let out = fmt_report(err.into());
+    println!("Error: {}", out);
+    let expected = r#"
+  × oops!
+   ╭─[bad_file.rs:1:7]
+ 1 │ ╭─▶ first line
+ 2 │ ├─▶ second line
+   · ╰───────┴─ ends here
+   ╰────
+"#
+    .to_string();
+    assert_eq!(expected, out);
+    Ok(())
+}
+
+#[test]
+fn multiline_highlight_ends_after_zero_width_char() -> Result<(), MietteError> {
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("oops!")]
+    struct MyBad {
+        #[source_code]
+        src: NamedSource<String>,
+        #[label = "ends here"]
+        highlight: SourceSpan,
+    }
+
+    let src = "first line\n\u{301}second line".to_string();
+    let err = MyBad {
+        src: NamedSource::new("bad_file.rs", src),
+        highlight: (6, 7).into(),
+    };
+    let out = fmt_report(err.into());
+    println!("Error: {}", out);
+    let expected = r#"
+  × oops!
+   ╭─[bad_file.rs:1:7]
+ 1 │ ╭─▶ first line
+ 2 │ ├─▶ ́second line
+   · ╰───┴─ ends here
+   ╰────
+"#
+    .to_string();
+    assert_eq!(expected, out);
+    Ok(())
+}
+
 #[test]
 fn multiline_highlight_no_label() -> Result<(), MietteError> {
     #[derive(Debug, Diagnostic, Error)]
@@ -1334,26 +1638,78 @@ line5
 }
 
 #[test]
-fn multiple_multiline_highlights_adjacent() -> Result<(), MietteError> {
+fn trim_trailing_whitespace() -> Result<(), MietteError> {
     #[derive(Debug, Diagnostic, Error)]
-    #[error("oops!")]
-    #[diagnostic(code(oops::my::bad), help("try doing it better next time?"))]
+    #[error("wtf?!")]
+    #[diagnostic(code(oops::my::bad))]
     struct MyBad {
+        #[source]
+        source: Inner,
         #[source_code]
         src: NamedSource<String>,
-        #[label = "this bit here"]
-        highlight1: SourceSpan,
-        #[label = "also this bit"]
-        highlight2: SourceSpan,
+        #[label]
+        highlight: SourceSpan,
     }
 
-    let src = "source\n  text\n    here\nmore here".to_string();
+    #[derive(Debug, Error)]
+    #[error("something went wrong\n\nHere's why.\n")]
+    struct Inner(#[source] InnerInner);
+
+    #[derive(Debug, Error)]
+    #[error("very much went wrong")]
+    struct InnerInner;
+
     let err = MyBad {
-        src: NamedSource::new("bad_file.rs", src),
-        highlight1: (0, 10).into(),
-        highlight2: (20, 6).into(),
+        source: Inner(InnerInner),
+        src: NamedSource::new("bad_file.rs", "line1\n\nline3\n".to_string()),
+        highlight: (0, 12).into(),
     };
-    let out = fmt_report(err.into());
+    let out = fmt_report_with_settings(err.into(), |handler| {
+        handler
+            .without_syntax_highlighting()
+            .with_trim_trailing_whitespace(true)
+    });
+    println!("Error: {}", out);
+    assert!(out.lines().all(|line| !line.ends_with(' ')));
+    let expected = "oops::my::bad
+
+  × wtf?!
+  ├─▶ something went wrong
+  │
+  │   Here's why.
+  │
+  ╰─▶ very much went wrong
+   ╭─[bad_file.rs:1:1]
+ 1 │ ╭─▶ line1
+ 2 │ │
+ 3 │ ╰─▶ line3
+   ╰────
+";
+    assert_eq!(expected, out);
+    Ok(())
+}
+
+#[test]
+fn multiple_multiline_highlights_adjacent() -> Result<(), MietteError> {
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("oops!")]
+    #[diagnostic(code(oops::my::bad), help("try doing it better next time?"))]
+    struct MyBad {
+        #[source_code]
+        src: NamedSource<String>,
+        #[label = "this bit here"]
+        highlight1: SourceSpan,
+        #[label = "also this bit"]
+        highlight2: SourceSpan,
+    }
+
+    let src = "source\n  text\n    here\nmore here".to_string();
+    let err = MyBad {
+        src: NamedSource::new("bad_file.rs", src),
+        highlight1: (0, 10).into(),
+        highlight2: (20, 6).into(),
+    };
+    let out = fmt_report(err.into());
     println!("Error: {}", out);
     let expected = "oops::my::bad
 
@@ -1361,10 +1717,10 @@ fn multiple_multiline_highlights_adjacent() -> Result<(), MietteError> {
    ╭─[bad_file.rs:1:1]
  1 │ ╭─▶ source
  2 │ ├─▶   text
-   · ╰──── this bit here
+   · ╰─────┴─ this bit here
  3 │ ╭─▶     here
  4 │ ├─▶ more here
-   · ╰──── also this bit
+   · ╰─────┴─ also this bit
    ╰────
   help: try doing it better next time?
 "
@@ -1566,6 +1922,31 @@ Error: oops::my::bad
     Ok(())
 }
 
+#[test]
+fn tuple_as_related() -> Result<(), MietteError> {
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("first oops!")]
+    #[diagnostic(code(oops::first))]
+    struct First;
+
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("second oops!")]
+    #[diagnostic(severity(Warning))]
+    struct Second;
+
+    let out = fmt_report_with_settings((First, Second).into(), |handler| handler);
+    println!("Error: {}", out);
+    let expected = r#"oops::first
+
+  × first oops!
+
+Warning: 
+  ⚠ second oops!
+"#;
+    assert_eq!(expected, out);
+    Ok(())
+}
+
 #[test]
 fn related_source_code_propagation() -> Result<(), MietteError> {
     #[derive(Debug, Diagnostic, Error)]
@@ -1755,75 +2136,128 @@ Advice: oops::my::related::advice
 }
 
 #[test]
-fn zero_length_eol_span() {
-    #[derive(Error, Debug, Diagnostic)]
+fn related_grouped_by_file() -> Result<(), MietteError> {
+    #[derive(Debug, Diagnostic, Error)]
     #[error("oops!")]
-    #[diagnostic(severity(Error))]
+    #[diagnostic(code(oops::my::bad))]
     struct MyBad {
         #[source_code]
-        src: NamedSource<&'static str>,
-        #[label("This bit here")]
-        bad_bit: SourceSpan,
+        src: NamedSource<String>,
+        #[label("this bit here")]
+        highlight: SourceSpan,
+        #[related]
+        related: Vec<MyBad>,
     }
+
+    let src = "source\n  text\n    here".to_string();
+    let related = |name: &str, highlight: (usize, usize)| MyBad {
+        src: NamedSource::new(name, src.clone()),
+        highlight: highlight.into(),
+        related: vec![],
+    };
     let err = MyBad {
-        src: NamedSource::new("issue", "this is the first line\nthis is the second line"),
-        bad_bit: (23, 0).into(),
+        src: NamedSource::new("a.rs", src.clone()),
+        highlight: (9, 4).into(),
+        related: vec![
+            related("a.rs", (0, 6)),
+            related("b.rs", (0, 6)),
+            related("a.rs", (9, 4)),
+        ],
     };
-    let out = fmt_report(err.into());
-    println!("Error: {}", out);
+    let out = fmt_report_with_settings(err.into(), |handler| {
+        handler
+            .without_syntax_highlighting()
+            .with_group_related_by_file(true)
+    });
+    let expected = r#"oops::my::bad
 
-    let expected = r#"
   × oops!
-   ╭─[issue:2:1]
- 1 │ this is the first line
- 2 │ this is the second line
-   · ▲
-   · ╰── This bit here
+   ╭─[a.rs:2:3]
+ 1 │ source
+ 2 │   text
+   ·   ──┬─
+   ·     ╰── this bit here
+ 3 │     here
+   ╰────
+
+[a.rs]
+
+Error: oops::my::bad
+
+  × oops!
+   ╭─[a.rs:1:1]
+ 1 │ source
+   · ───┬──
+   ·    ╰── this bit here
+ 2 │   text
+   ╰────
+
+Error: oops::my::bad
+
+  × oops!
+   ╭─[a.rs:2:3]
+ 1 │ source
+ 2 │   text
+   ·   ──┬─
+   ·     ╰── this bit here
+ 3 │     here
+   ╰────
+
+[b.rs]
+
+Error: oops::my::bad
+
+  × oops!
+   ╭─[b.rs:1:1]
+ 1 │ source
+   · ───┬──
+   ·    ╰── this bit here
+ 2 │   text
    ╰────
 "#
+    .trim_start()
     .to_string();
-
     assert_eq!(expected, out);
+    Ok(())
 }
 
 #[test]
-fn primary_label() {
-    #[derive(Error, Debug, Diagnostic)]
-    #[error("oops!")]
+fn related_indent() -> Result<(), MietteError> {
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("{msg}")]
     struct MyBad {
-        #[source_code]
-        src: NamedSource<&'static str>,
-        #[label]
-        first_label: SourceSpan,
-        #[label(primary, "nope")]
-        second_label: SourceSpan,
+        msg: &'static str,
+        #[related]
+        related: Vec<MyBad>,
     }
+
     let err = MyBad {
-        src: NamedSource::new("issue", "this is the first line\nthis is the second line"),
-        first_label: (2, 4).into(),
-        second_label: (24, 4).into(),
+        msg: "outer",
+        related: vec![MyBad {
+            msg: "middle",
+            related: vec![MyBad {
+                msg: "inner",
+                related: vec![],
+            }],
+        }],
     };
-    let out = fmt_report(err.into());
-    println!("Error: {}", out);
-
-    // line 2 should be the primary, not line 1
+    let out = fmt_report_with_settings(err.into(), |handler| handler.with_related_indent(4));
     let expected = r#"
-  × oops!
-   ╭─[issue:2:2]
- 1 │ this is the first line
-   ·   ────
- 2 │ this is the second line
-   ·  ──┬─
-   ·    ╰── nope
-   ╰────
+  × outer
+
+Error: 
+  × middle
+
+    Error: 
+      × inner
 "#
     .to_string();
-
     assert_eq!(expected, out);
+    Ok(())
 }
 
 #[test]
-fn single_line_with_wide_char_unaligned_span_start() -> Result<(), MietteError> {
+fn prefix_banner() -> Result<(), MietteError> {
     #[derive(Debug, Diagnostic, Error)]
     #[error("oops!")]
     #[diagnostic(code(oops::my::bad), help("try doing it better next time?"))]
@@ -1834,111 +2268,2057 @@ fn single_line_with_wide_char_unaligned_span_start() -> Result<(), MietteError>
         highlight: SourceSpan,
     }
 
-    let src = "source\n  👼🏼text\n    here".to_string();
+    let src = "source\n  text\n    here".to_string();
     let err = MyBad {
         src: NamedSource::new("bad_file.rs", src),
-        highlight: (10, 5).into(),
+        highlight: (9, 4).into(),
     };
-    let out = fmt_report(err.into());
-    println!("Error: {}", out);
-    let expected = r#"oops::my::bad
+    let out = fmt_report_with_settings(err.into(), |handler| {
+        handler
+            .without_syntax_highlighting()
+            .with_prefix("==> my-app (2024-01-01T00:00:00Z) <==".into())
+    });
+    let expected = r#"==> my-app (2024-01-01T00:00:00Z) <==
+oops::my::bad
 
   × oops!
-   ╭─[bad_file.rs:2:4]
+   ╭─[bad_file.rs:2:3]
  1 │ source
- 2 │   👼🏼text
+ 2 │   text
    ·   ──┬─
    ·     ╰── this bit here
  3 │     here
    ╰────
   help: try doing it better next time?
 "#
-    .trim_start()
     .to_string();
     assert_eq!(expected, out);
     Ok(())
 }
 
 #[test]
-fn single_line_with_wide_char_unaligned_span_end() -> Result<(), MietteError> {
+fn cause_codes() -> Result<(), MietteError> {
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("inner error")]
+    #[diagnostic(code(inner::code))]
+    struct Inner;
+
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("outer error")]
+    #[diagnostic(code(outer::code))]
+    struct Outer {
+        #[diagnostic_source]
+        inner: Inner,
+    }
+
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("io went wrong")]
+    struct StdSource {
+        #[source]
+        inner: MietteError,
+    }
+
+    let out = fmt_report_with_settings(Outer { inner: Inner }.into(), |handler| {
+        handler.with_render_cause_codes(true)
+    });
+    let expected = r#"outer::code
+
+  × outer error
+  ╰─▶   × [inner::code] inner error
+      
+"#
+    .to_string();
+    assert_eq!(expected, out);
+
+    let err = StdSource {
+        inner: MietteError::OutOfBounds,
+    };
+    let out = fmt_report_with_settings(err.into(), |handler| handler.with_render_cause_codes(true));
+    let expected = r#"
+  × io went wrong
+  ╰─▶ [miette::span_out_of_bounds] The given offset is outside the bounds of its Source
+"#
+    .to_string();
+    assert_eq!(expected, out);
+    Ok(())
+}
+
+#[test]
+fn related_spacing() -> Result<(), MietteError> {
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("{0}")]
+    struct Related(&'static str);
+
     #[derive(Debug, Diagnostic, Error)]
     #[error("oops!")]
-    #[diagnostic(code(oops::my::bad), help("try doing it better next time?"))]
     struct MyBad {
-        #[source_code]
-        src: NamedSource<String>,
-        #[label("this bit here")]
-        highlight: SourceSpan,
+        #[related]
+        related: Vec<Related>,
     }
 
-    let src = "source\n  text 👼🏼\n    here".to_string();
     let err = MyBad {
-        src: NamedSource::new("bad_file.rs", src),
-        highlight: (9, 6).into(),
+        related: vec![Related("first"), Related("second")],
     };
-    let out = fmt_report(err.into());
-    println!("Error: {}", out);
-    let expected = r#"oops::my::bad
-
+    let out = fmt_report_with_settings(err.into(), |handler| handler.with_related_spacing(0));
+    let expected = r#"
   × oops!
-   ╭─[bad_file.rs:2:3]
- 1 │ source
- 2 │   text 👼🏼
-   ·   ───┬───
-   ·      ╰── this bit here
- 3 │     here
-   ╰────
-  help: try doing it better next time?
+Error: 
+  × first
+Error: 
+  × second
 "#
-    .trim_start()
     .to_string();
     assert_eq!(expected, out);
     Ok(())
 }
 
 #[test]
-fn single_line_with_wide_char_unaligned_span_empty() -> Result<(), MietteError> {
+fn related_separator() -> Result<(), MietteError> {
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("{0}")]
+    struct Related(&'static str);
+
     #[derive(Debug, Diagnostic, Error)]
     #[error("oops!")]
-    #[diagnostic(code(oops::my::bad), help("try doing it better next time?"))]
     struct MyBad {
-        #[source_code]
-        src: NamedSource<String>,
-        #[label("this bit here")]
-        highlight: SourceSpan,
+        #[related]
+        related: Vec<Related>,
     }
 
-    let src = "source\n  👼🏼text\n    here".to_string();
     let err = MyBad {
-        src: NamedSource::new("bad_file.rs", src),
-        highlight: (10, 0).into(),
+        related: vec![Related("first"), Related("second")],
     };
-    let out = fmt_report(err.into());
+    let out = fmt_report_with_settings(err.into(), |handler| handler.with_related_separator("---"));
     println!("Error: {}", out);
-    let expected = r#"oops::my::bad
-
+    let expected = r#"
   × oops!
-   ╭─[bad_file.rs:2:4]
- 1 │ source
- 2 │   👼🏼text
-   ·   ▲
-   ·   ╰── this bit here
- 3 │     here
-   ╰────
-  help: try doing it better next time?
+
+Error: 
+  × first
+---
+
+Error: 
+  × second
 "#
-    .trim_start()
     .to_string();
     assert_eq!(expected, out);
     Ok(())
 }
 
 #[test]
-#[cfg(feature = "syntect-highlighter")]
-fn syntax_highlighter() {
-    std::env::set_var("REPLACE_TABS", "4");
-    #[derive(Debug, Error, Diagnostic)]
-    #[error("This is an error")]
+fn related_first() -> Result<(), MietteError> {
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("{0}")]
+    struct Related(&'static str);
+
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("oops!")]
+    #[diagnostic(help("try doing it better next time?"))]
+    struct MyBad {
+        #[related]
+        related: Vec<Related>,
+    }
+
+    let err = MyBad {
+        related: vec![Related("first"), Related("second")],
+    };
+    let out = fmt_report_with_settings(err.into(), |handler| handler.with_related_first(true));
+    println!("Error: {}", out);
+    let expected = r#"
+  × oops!
+
+Error: 
+  × first
+
+Error: 
+  × second
+  help: try doing it better next time?
+"#
+    .to_string();
+    assert_eq!(expected, out);
+    Ok(())
+}
+
+#[test]
+fn related_count_in_header() -> Result<(), MietteError> {
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("{0}")]
+    struct Related(&'static str);
+
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("oops!")]
+    #[diagnostic(code(oops::my::bad))]
+    struct MyBad {
+        #[related]
+        related: Vec<Related>,
+    }
+
+    let err = MyBad {
+        related: vec![Related("first"), Related("second"), Related("third")],
+    };
+    let out = fmt_report_with_settings(err.into(), |handler| {
+        handler
+            .with_related_count_in_header(true)
+            .with_render_related_header(false)
+    });
+    println!("Error: {}", out);
+    let expected = r#"oops::my::bad (+3 related)
+
+  × oops!
+  × first
+  × second
+  × third
+"#;
+    assert_eq!(expected, out);
+    Ok(())
+}
+
+#[test]
+fn related_without_header() -> Result<(), MietteError> {
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("oops!")]
+    #[diagnostic(help("try doing it better next time?"))]
+    struct MyBad {
+        #[source_code]
+        src: NamedSource<String>,
+        #[label("this bit here")]
+        highlight: SourceSpan,
+        #[related]
+        related: Vec<MyBad>,
+    }
+
+    let src = "source\n  text\n    here".to_string();
+    let err = MyBad {
+        src: NamedSource::new("bad_file.rs", src.clone()),
+        highlight: (9, 4).into(),
+        related: vec![MyBad {
+            src: NamedSource::new("bad_file.rs", src),
+            highlight: (0, 6).into(),
+            related: vec![],
+        }],
+    };
+    let out = fmt_report_with_settings(err.into(), |handler| {
+        handler
+            .without_syntax_highlighting()
+            .with_render_related_header(false)
+    });
+    let expected = r#"
+  × oops!
+   ╭─[bad_file.rs:2:3]
+ 1 │ source
+ 2 │   text
+   ·   ──┬─
+   ·     ╰── this bit here
+ 3 │     here
+   ╰────
+  help: try doing it better next time?
+  × oops!
+   ╭─[bad_file.rs:1:1]
+ 1 │ source
+   · ───┬──
+   ·    ╰── this bit here
+ 2 │   text
+   ╰────
+  help: try doing it better next time?
+"#
+    .to_string();
+    assert_eq!(expected, out);
+    Ok(())
+}
+
+#[test]
+fn related_without_header_keeps_url() -> Result<(), MietteError> {
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("oops!")]
+    struct MyBad {
+        #[related]
+        related: Vec<Linked>,
+    }
+
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("see the docs")]
+    #[diagnostic(url("https://example.com"))]
+    struct Linked;
+
+    let err = MyBad {
+        related: vec![Linked],
+    };
+    let out = fmt_report_with_settings(err.into(), |handler| {
+        handler.with_render_related_header(false)
+    });
+    assert!(out.contains("https://example.com"));
+    assert!(out.contains("(link)"));
+    Ok(())
+}
+
+#[test]
+fn render_order_related() -> Result<(), MietteError> {
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("oops!")]
+    #[diagnostic(help("try doing it better next time?"))]
+    struct MyBad {
+        #[related]
+        related: Vec<Related>,
+    }
+
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("also this")]
+    #[diagnostic(help("and fix this too"))]
+    struct Related;
+
+    let err = MyBad {
+        related: vec![Related],
+    };
+    let out = fmt_report_with_settings(err.into(), |handler| {
+        handler.with_render_order(&[Section::Help, Section::Message, Section::Related])
+    });
+    println!("Error: {}", out);
+    let expected = r#"  help: try doing it better next time?
+  × oops!
+
+  help: and fix this too
+  × also this
+"#;
+    assert_eq!(expected, out);
+    Ok(())
+}
+
+#[test]
+fn bold_message() -> Result<(), MietteError> {
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("oops!")]
+    struct MyBad;
+
+    let mut out = String::new();
+    GraphicalReportHandler::new_themed(GraphicalTheme::unicode())
+        .with_bold_message(true)
+        .render_report(&mut out, &MyBad)
+        .unwrap();
+    println!("Error: {:?}", out);
+    assert!(out.contains("\u{1b}[31;1moops!\u{1b}[0m"));
+
+    let mut out = String::new();
+    GraphicalReportHandler::new_themed(GraphicalTheme::unicode())
+        .render_report(&mut out, &MyBad)
+        .unwrap();
+    assert!(!out.contains("\u{1b}[31;1m"));
+    Ok(())
+}
+
+#[test]
+fn multiple_primary_labels() -> Result<(), MietteError> {
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("oops!")]
+    struct MyBad {
+        #[source_code]
+        src: NamedSource<String>,
+        #[label(primary, "first")]
+        first: SourceSpan,
+        #[label("middle")]
+        middle: SourceSpan,
+        #[label(primary, "second")]
+        second: SourceSpan,
+    }
+
+    let src = "source\n  text\n    here".to_string();
+    let err = MyBad {
+        src: NamedSource::new("bad_file.rs", src),
+        first: (0, 6).into(),
+        middle: (9, 4).into(),
+        second: (18, 4).into(),
+    };
+    let mut out = String::new();
+    GraphicalReportHandler::new_themed(GraphicalTheme::unicode())
+        .with_primary_style(owo_colors::Style::new().red().bold())
+        .render_report(&mut out, &err)
+        .unwrap();
+    println!("Error: {:?}", out);
+    assert!(out.contains("\u{1b}[31;1mfirst\u{1b}[0m"));
+    assert!(out.contains("\u{1b}[31;1msecond\u{1b}[0m"));
+    assert!(out.contains("\u{1b}[35;1mmiddle\u{1b}[0m"));
+
+    // Without a primary style, primary labels cycle like any other.
+    let mut out = String::new();
+    GraphicalReportHandler::new_themed(GraphicalTheme::unicode())
+        .render_report(&mut out, &err)
+        .unwrap();
+    println!("Error: {:?}", out);
+    assert!(out.contains("\u{1b}[35;1mfirst\u{1b}[0m"));
+    assert!(out.contains("\u{1b}[33;1mmiddle\u{1b}[0m"));
+    assert!(out.contains("\u{1b}[32;1msecond\u{1b}[0m"));
+    Ok(())
+}
+
+#[test]
+fn severity_colors() -> Result<(), MietteError> {
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("oops!")]
+    #[diagnostic(severity(Warning))]
+    struct MyBad;
+
+    let mut out = String::new();
+    GraphicalReportHandler::new_themed(GraphicalTheme::unicode())
+        .with_severity_colors(
+            owo_colors::Style::new().red(),
+            owo_colors::Style::new().blue(),
+            owo_colors::Style::new().green(),
+        )
+        .render_report(&mut out, &MyBad)
+        .unwrap();
+    println!("Error: {:?}", out);
+    assert!(out.contains("\u{1b}[34m⚠\u{1b}[0m"));
+
+    // Changing the theme afterwards keeps the overrides.
+    let mut out = String::new();
+    GraphicalReportHandler::new()
+        .with_severity_colors(
+            owo_colors::Style::new().red(),
+            owo_colors::Style::new().blue(),
+            owo_colors::Style::new().green(),
+        )
+        .with_theme(GraphicalTheme::unicode())
+        .render_report(&mut out, &MyBad)
+        .unwrap();
+    println!("Error: {:?}", out);
+    assert!(out.contains("\u{1b}[34m⚠\u{1b}[0m"));
+    Ok(())
+}
+
+#[test]
+fn focus() -> Result<(), MietteError> {
+    let report = Report::from(
+        miette::MietteDiagnostic::new("oops!").with_label(LabeledSpan::at(8..9, "here")),
+    )
+    .with_source_code("let x = 1;");
+    let mut out = String::new();
+    GraphicalReportHandler::new_themed(GraphicalTheme::unicode())
+        .with_focus(4..9)
+        .render_report(&mut out, report.as_ref())
+        .unwrap();
+    println!("Error: {:?}", out);
+    assert!(out.contains("\u{1b}[2mlet \u{1b}[0mx = 1\u{1b}[2m;\u{1b}[0m\n"));
+
+    let mut out = String::new();
+    GraphicalReportHandler::new_themed(GraphicalTheme::unicode_nocolor())
+        .with_focus(4..9)
+        .render_report(&mut out, report.as_ref())
+        .unwrap();
+    assert!(out.contains("1 │ let x = 1;\n"));
+
+    let mut out = String::new();
+    GraphicalReportHandler::new_themed(GraphicalTheme::unicode_nocolor())
+        .with_focus(4..9)
+        .with_dimmed_style(owo_colors::Style::new().italic())
+        .render_report(&mut out, report.as_ref())
+        .unwrap();
+    println!("Error: {:?}", out);
+    assert!(out.contains("\u{1b}[3mlet \u{1b}[0mx = 1\u{1b}[3m;\u{1b}[0m\n"));
+    Ok(())
+}
+
+#[test]
+fn severity_from_code() -> Result<(), MietteError> {
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("oops!")]
+    #[diagnostic(code(warn::x))]
+    struct MyBad;
+
+    let out = fmt_report_with_settings(MyBad.into(), |handler| {
+        handler.with_severity_from_code(|code| {
+            code.starts_with("warn::")
+                .then_some(miette::Severity::Warning)
+        })
+    });
+    println!("Error: {}", out);
+    let expected = "warn::x\n\n  ⚠ oops!\n";
+    assert_eq!(expected, out);
+    Ok(())
+}
+
+#[test]
+fn cow_source() -> Result<(), MietteError> {
+    use std::borrow::Cow;
+
+    let expected = r#"
+  × oops!
+   ╭────
+ 1 │ let x = 1;
+   ·     ┬
+   ·     ╰── here
+   ╰────
+"#;
+    for src in [
+        Cow::Borrowed("let x = 1;"),
+        Cow::Owned("let x = 1;".to_string()),
+    ] {
+        let report = Report::from(
+            miette::MietteDiagnostic::new("oops!").with_label(LabeledSpan::at(4..5, "here")),
+        )
+        .with_source_code(src);
+        let out = fmt_report(report);
+        println!("Error: {}", out);
+        assert_eq!(expected, out);
+    }
+    Ok(())
+}
+
+#[test]
+fn render_order() -> Result<(), MietteError> {
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("oops!")]
+    #[diagnostic(code(oops::my::bad), help("try doing it better next time?"))]
+    struct MyBad {
+        #[source_code]
+        src: NamedSource<String>,
+        #[label("this bit here")]
+        highlight: SourceSpan,
+    }
+
+    let src = "source\n  text\n    here".to_string();
+    let err = MyBad {
+        src: NamedSource::new("bad_file.rs", src),
+        highlight: (9, 4).into(),
+    };
+    let out = fmt_report_with_settings(err.into(), |handler| {
+        handler.without_syntax_highlighting().with_render_order(&[
+            Section::Snippets,
+            Section::Message,
+            Section::Help,
+        ])
+    });
+    println!("Error: {}", out);
+    let expected = r#"   ╭─[bad_file.rs:2:3]
+ 1 │ source
+ 2 │   text
+   ·   ──┬─
+   ·     ╰── this bit here
+ 3 │     here
+   ╰────
+  × oops!
+  help: try doing it better next time?
+"#;
+    assert_eq!(expected, out);
+    Ok(())
+}
+
+#[test]
+fn deletion_label() -> Result<(), MietteError> {
+    let report = Report::from(
+        miette::MietteDiagnostic::new("extra semicolon").with_label(LabeledSpan::deletion(10..11)),
+    )
+    .with_source_code("let x = 1;;");
+    let mut out = String::new();
+    GraphicalReportHandler::new_themed(GraphicalTheme::unicode())
+        .render_report(&mut out, report.as_ref())
+        .unwrap();
+    println!("Error: {:?}", out);
+    assert!(out.contains("let x = 1;\u{1b}[9m;\u{1b}[0m"));
+    assert!(out.contains("remove this"));
+
+    let mut out = String::new();
+    GraphicalReportHandler::new_themed(GraphicalTheme::unicode_nocolor())
+        .with_deletion_style(owo_colors::Style::new().underline())
+        .render_report(&mut out, report.as_ref())
+        .unwrap();
+    println!("Error: {:?}", out);
+    assert!(out.contains("let x = 1;\u{1b}[4m;\u{1b}[0m"));
+
+    let out = fmt_report(report);
+    assert!(out.contains("1 │ let x = 1;;\n"));
+    Ok(())
+}
+
+#[test]
+fn context_with_source() -> Result<(), MietteError> {
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("unexpected token")]
+    struct ParseError {
+        #[label("here")]
+        span: SourceSpan,
+    }
+
+    let report = Report::new(ParseError {
+        span: (4, 1).into(),
+    })
+    .context_with_source("failed to parse config", "key = ;");
+    let out = fmt_report(report);
+    println!("Error: {}", out);
+    let expected = r#"
+  × failed to parse config
+  ╰─▶ unexpected token
+   ╭────
+ 1 │ key = ;
+   ·     ┬
+   ·     ╰── here
+   ╰────
+"#;
+    assert_eq!(expected, out);
+    Ok(())
+}
+
+#[test]
+fn map_labels() -> Result<(), MietteError> {
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("unexpected token")]
+    struct ParseError {
+        #[label("here")]
+        span: SourceSpan,
+    }
+
+    // The error was found while parsing just the second line.
+    let report = Report::new(ParseError {
+        span: (4, 1).into(),
+    })
+    .map_labels(|label| {
+        LabeledSpan::new_with_span(
+            label.label().map(String::from),
+            (label.offset() + 10, label.len()),
+        )
+    })
+    .with_source_code("[section]\nkey = ;");
+    let out = fmt_report(report);
+    println!("Error: {}", out);
+    let expected = r#"
+  × unexpected token
+   ╭─[2:5]
+ 1 │ [section]
+ 2 │ key = ;
+   ·     ┬
+   ·     ╰── here
+   ╰────
+"#;
+    assert_eq!(expected, out);
+    Ok(())
+}
+
+#[test]
+fn secondary_underline() -> Result<(), MietteError> {
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("oops!")]
+    struct MyBad {
+        #[source_code]
+        src: String,
+        #[label(primary, "primary")]
+        first: SourceSpan,
+        #[label("secondary")]
+        second: SourceSpan,
+    }
+
+    let err = MyBad {
+        src: "source text here".to_string(),
+        first: (0, 6).into(),
+        second: (12, 4).into(),
+    };
+    let mut out = String::new();
+    GraphicalReportHandler::new_themed(GraphicalTheme::unicode_nocolor())
+        .with_secondary_underline('╌')
+        .render_report(&mut out, &err)
+        .unwrap();
+    println!("Error: {}", out);
+    let expected = r#"
+  × oops!
+   ╭────
+ 1 │ source text here
+   · ───┬──      ╌╌┬╌
+   ·    │          ╰── secondary
+   ·    ╰── primary
+   ╰────
+"#;
+    assert_eq!(expected, out);
+    Ok(())
+}
+
+#[test]
+fn without_render_causes() -> Result<(), MietteError> {
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("inner problem")]
+    struct Inner;
+
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("oops!")]
+    #[diagnostic(help("try doing it better next time?"))]
+    struct MyBad {
+        #[source]
+        inner: Inner,
+    }
+
+    let out = fmt_report_with_settings(MyBad { inner: Inner }.into(), |handler| {
+        handler.with_render_causes(false)
+    });
+    println!("Error: {}", out);
+    let expected = r#"
+  × oops!
+  help: try doing it better next time?
+"#;
+    assert_eq!(expected, out);
+    Ok(())
+}
+
+#[test]
+fn label_legend() -> Result<(), MietteError> {
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("oops!")]
+    struct MyBad {
+        #[source_code]
+        src: String,
+        #[label("one")]
+        first: SourceSpan,
+        #[label("two")]
+        second: SourceSpan,
+        #[label("three")]
+        third: SourceSpan,
+    }
+
+    let err = MyBad {
+        src: "source text here".to_string(),
+        first: (0, 6).into(),
+        second: (7, 4).into(),
+        third: (12, 4).into(),
+    };
+    let mut out = String::new();
+    GraphicalReportHandler::new_themed(GraphicalTheme::unicode())
+        .with_label_legend(true)
+        .render_report(&mut out, &err)
+        .unwrap();
+    println!("Error: {}", out);
+    assert!(out.contains("  \u{1b}[35;1m───\u{1b}[0m \u{1b}[35;1mone\u{1b}[0m\n"));
+    assert!(out.contains("  \u{1b}[33;1m───\u{1b}[0m \u{1b}[33;1mtwo\u{1b}[0m\n"));
+    assert!(out.contains("  \u{1b}[32;1m───\u{1b}[0m \u{1b}[32;1mthree\u{1b}[0m\n"));
+
+    let out = fmt_report_with_settings(err.into(), |handler| handler.with_label_legend(true));
+    let expected = r#"
+  × oops!
+   ╭────
+ 1 │ source text here
+   · ───┬── ──┬─ ──┬─
+   ·    │     │    ╰── three
+   ·    │     ╰── two
+   ·    ╰── one
+   ╰────
+  ─── one
+  ─── two
+  ─── three
+"#;
+    assert_eq!(expected, out);
+    Ok(())
+}
+
+#[test]
+fn numbered_labels() -> Result<(), MietteError> {
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("{} conflicts with {}", LabelRef(2), LabelRef(1))]
+    struct MyBad {
+        #[source_code]
+        src: String,
+        #[label("first defined here")]
+        first: SourceSpan,
+        #[label("redefined here")]
+        second: SourceSpan,
+    }
+
+    let err = MyBad {
+        src: "let x = 1; let x = 2;".to_string(),
+        first: (4, 1).into(),
+        second: (15, 1).into(),
+    };
+    let out = fmt_report_with_settings(err.into(), |handler| handler.with_numbered_labels(true));
+    println!("Error: {}", out);
+    let expected = r#"
+  × [2] conflicts with [1]
+   ╭────
+ 1 │ let x = 1; let x = 2;
+   ·     ┬          ┬
+   ·     │          ╰── [2] redefined here
+   ·     ╰── [1] first defined here
+   ╰────
+"#;
+    assert_eq!(expected, out);
+    Ok(())
+}
+
+#[test]
+fn wrap_ignores_ansi_escapes() -> Result<(), MietteError> {
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("oops!")]
+    #[diagnostic(help(
+        "\u{1b}[31mred\u{1b}[0m \u{1b}[32mgreen\u{1b}[0m \u{1b}[34mblue\u{1b}[0m words that wrap"
+    ))]
+    struct MyBad;
+
+    let mut out = String::new();
+    GraphicalReportHandler::new_themed(GraphicalTheme::unicode_nocolor())
+        .with_width(30)
+        .render_report(&mut out, &MyBad)
+        .unwrap();
+    println!("Error: {}", out);
+    let expected = "
+  × oops!
+  help: \u{1b}[31mred\u{1b}[0m \u{1b}[32mgreen\u{1b}[0m \u{1b}[34mblue\u{1b}[0m words
+        that wrap
+";
+    assert_eq!(expected, out);
+    Ok(())
+}
+
+#[test]
+fn colored_causes_wrap_like_uncolored() -> Result<(), MietteError> {
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("inner problem")]
+    #[diagnostic(help("a fairly long piece of help text that has to wrap somewhere"))]
+    struct Inner;
+
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("oops!")]
+    struct MyBad {
+        #[diagnostic_source]
+        inner: Inner,
+    }
+
+    let render = |theme| {
+        let mut out = String::new();
+        GraphicalReportHandler::new_themed(theme)
+            .with_width(40)
+            .render_report(&mut out, &MyBad { inner: Inner })
+            .unwrap();
+        out
+    };
+    let colored = render(GraphicalTheme::unicode());
+    let uncolored = render(GraphicalTheme::unicode_nocolor());
+    println!("Error: {}", colored);
+    let stripped = String::from_utf8(strip_ansi_escapes::strip(colored)).unwrap();
+    assert_eq!(uncolored, stripped);
+    Ok(())
+}
+
+#[test]
+fn gutter_separator_and_align() -> Result<(), MietteError> {
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("oops!")]
+    struct MyBad {
+        #[source_code]
+        src: NamedSource<String>,
+        #[label("this bit here")]
+        highlight: SourceSpan,
+    }
+
+    let src = "1\n2\n3\n4\n5\n6\n7\n8\n9\nten\n11\n".to_string();
+    let err = MyBad {
+        src: NamedSource::new("bad_file.rs", src),
+        highlight: (16, 1).into(),
+    };
+    let out = fmt_report_with_settings(err.into(), |handler| {
+        handler
+            .without_syntax_highlighting()
+            .with_gutter_separator(':')
+    });
+    println!("Error: {}", out);
+    let expected = r#"
+  × oops!
+    ╭─[bad_file.rs:9:1]
+  8 : 8
+  9 : 9
+    · ┬
+    · ╰── this bit here
+ 10 : ten
+    ╰────
+"#
+    .to_string();
+    assert_eq!(expected, out);
+
+    let err = MyBad {
+        src: NamedSource::new("bad_file.rs", "1\n2\n3\n4\n5\n6\n7\n8\n9\nten\n11\n".into()),
+        highlight: (16, 1).into(),
+    };
+    let out = fmt_report_with_settings(err.into(), |handler| {
+        handler
+            .without_syntax_highlighting()
+            .with_gutter_separator(':')
+            .with_gutter_align(GutterAlign::Left)
+    });
+    println!("Error: {}", out);
+    let expected = r#"
+  × oops!
+    ╭─[bad_file.rs:9:1]
+ 8  : 8
+ 9  : 9
+    · ┬
+    · ╰── this bit here
+ 10 : ten
+    ╰────
+"#
+    .to_string();
+    assert_eq!(expected, out);
+    Ok(())
+}
+
+#[test]
+fn width_is_a_maximum() -> Result<(), MietteError> {
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("oops!")]
+    #[diagnostic(code(oops::my::bad), help("try doing it better next time?"))]
+    struct MyBad {
+        #[source_code]
+        src: NamedSource<String>,
+        #[label("this bit here")]
+        highlight: SourceSpan,
+    }
+
+    let err = MyBad {
+        src: NamedSource::new("bad_file.rs", "source\n  text\n    here".to_string()),
+        highlight: (9, 4).into(),
+    };
+    let out = fmt_report_with_settings(err.into(), |handler| handler.with_width(80));
+    println!("Error: {}", out);
+    for line in out.lines() {
+        assert!(!line.ends_with(' '), "line is padded: {:?}", line);
+        assert!(line.chars().count() < 40, "line is padded: {:?}", line);
+    }
+    Ok(())
+}
+
+#[test]
+fn max_width_caps_width() -> Result<(), MietteError> {
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("{}", "word ".repeat(50))]
+    struct MyBad;
+
+    let render = |handler: GraphicalReportHandler| {
+        let mut out = String::new();
+        handler.render_report(&mut out, &MyBad).unwrap();
+        out
+    };
+    let theme = GraphicalTheme::unicode_nocolor;
+    // As if the terminal reported a width of 300 columns.
+    let uncapped = render(GraphicalReportHandler::new_themed(theme()).with_width(300));
+    assert!(uncapped.lines().any(|line| line.chars().count() > 200));
+
+    let capped = render(
+        GraphicalReportHandler::new_themed(theme())
+            .with_width(300)
+            .with_max_width(100),
+    );
+    println!("Error: {}", capped);
+    assert!(capped.lines().all(|line| line.chars().count() <= 100));
+    assert!(capped.lines().any(|line| line.chars().count() > 90));
+
+    // The cap also applies when the width is set afterwards.
+    let capped_first = render(
+        GraphicalReportHandler::new_themed(theme())
+            .with_max_width(100)
+            .with_width(300),
+    );
+    assert_eq!(capped, capped_first);
+    Ok(())
+}
+
+#[test]
+fn compact_uses_title() -> Result<(), MietteError> {
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("the configuration file couldn't be parsed")]
+    #[diagnostic(
+        code(oops::my::bad),
+        title("bad config"),
+        help("try doing it better next time?")
+    )]
+    struct MyBad {
+        #[source_code]
+        src: NamedSource<String>,
+        #[label("this bit here")]
+        highlight: SourceSpan,
+    }
+
+    let err = MyBad {
+        src: NamedSource::new("bad_file.rs", "source\n  text\n    here".to_string()),
+        highlight: (9, 4).into(),
+    };
+    let out = fmt_report_with_settings(err.into(), |handler| handler.with_compact(true));
+    println!("Error: {}", out);
+    assert_eq!("  × bad config\n", out);
+
+    let err = MyBad {
+        src: NamedSource::new("bad_file.rs", "source\n  text\n    here".to_string()),
+        highlight: (9, 4).into(),
+    };
+    let out = fmt_report(err.into());
+    println!("Error: {}", out);
+    assert!(out.contains("  × the configuration file couldn't be parsed\n"));
+    assert!(!out.contains("bad config"));
+    Ok(())
+}
+
+#[test]
+fn render_sections() -> Result<(), MietteError> {
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("oops!")]
+    #[diagnostic(code(oops::my::bad), help("try doing it better next time?"))]
+    struct MyBad {
+        #[source_code]
+        src: NamedSource<String>,
+        #[label("this bit here")]
+        highlight: SourceSpan,
+        #[source]
+        source: Inner,
+        #[related]
+        related: Vec<Inner>,
+    }
+
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("inner oops!")]
+    struct Inner;
+
+    let err = MyBad {
+        src: NamedSource::new("bad_file.rs", "source\n  text\n    here".to_string()),
+        highlight: (9, 4).into(),
+        source: Inner,
+        related: vec![Inner],
+    };
+    let out = fmt_report_with_settings(err.into(), |handler| {
+        handler
+            .without_syntax_highlighting()
+            .with_footer("this is a footer".into())
+            .with_sections(RenderSections::HEADER | RenderSections::SNIPPETS)
+    });
+    println!("Error: {}", out);
+    let expected = r#"oops::my::bad
+
+  × oops!
+   ╭─[bad_file.rs:2:3]
+ 1 │ source
+ 2 │   text
+   ·   ──┬─
+   ·     ╰── this bit here
+ 3 │     here
+   ╰────
+"#
+    .to_string();
+    assert_eq!(expected, out);
+    Ok(())
+}
+
+#[test]
+fn zero_length_eol_span() {
+    #[derive(Error, Debug, Diagnostic)]
+    #[error("oops!")]
+    #[diagnostic(severity(Error))]
+    struct MyBad {
+        #[source_code]
+        src: NamedSource<&'static str>,
+        #[label("This bit here")]
+        bad_bit: SourceSpan,
+    }
+    let err = MyBad {
+        src: NamedSource::new("issue", "this is the first line\nthis is the second line"),
+        bad_bit: (23, 0).into(),
+    };
+    let out = fmt_report(err.into());
+    println!("Error: {}", out);
+
+    let expected = r#"
+  × oops!
+   ╭─[issue:2:1]
+ 1 │ this is the first line
+ 2 │ this is the second line
+   · ▲
+   · ╰── This bit here
+   ╰────
+"#
+    .to_string();
+
+    assert_eq!(expected, out);
+}
+
+#[test]
+fn empty_span_marker() {
+    #[derive(Error, Debug, Diagnostic)]
+    #[error("oops!")]
+    struct MyBad {
+        #[source_code]
+        src: NamedSource<&'static str>,
+        #[label("expected a value")]
+        bad_bit: SourceSpan,
+    }
+    let err = MyBad {
+        src: NamedSource::new("issue", "let x = ;"),
+        bad_bit: (8, 0).into(),
+    };
+    let out = fmt_report_with_settings(err.into(), |handler| handler.with_empty_span_marker('‸'));
+    println!("Error: {}", out);
+
+    let expected = r#"
+  × oops!
+   ╭─[issue:1:9]
+ 1 │ let x = ;
+   ·         ‸
+   ·         ╰── expected a value
+   ╰────
+"#
+    .to_string();
+
+    assert_eq!(expected, out);
+}
+
+#[test]
+fn cr_only_line_endings() -> Result<(), MietteError> {
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("oops!")]
+    #[diagnostic(code(oops::my::bad))]
+    struct MyBad {
+        #[source_code]
+        src: NamedSource<String>,
+        #[label("this bit here")]
+        highlight: SourceSpan,
+    }
+
+    let src = "source\r  text\r    here".to_string();
+    let err = MyBad {
+        src: NamedSource::new("bad_file.rs", src),
+        highlight: (9, 4).into(),
+    };
+    let out = fmt_report(err.into());
+    println!("Error: {}", out);
+    let expected = r#"oops::my::bad
+
+  × oops!
+   ╭─[bad_file.rs:2:3]
+ 1 │ source
+ 2 │   text
+   ·   ──┬─
+   ·     ╰── this bit here
+ 3 │     here
+   ╰────
+"#
+    .trim_start()
+    .to_string();
+    assert_eq!(expected, out);
+    Ok(())
+}
+
+#[test]
+fn always_show_header() -> Result<(), MietteError> {
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("oops!")]
+    #[diagnostic(severity(Warning))]
+    struct MyBad;
+
+    let out = fmt_report(MyBad.into());
+    println!("Error: {}", out);
+    assert_eq!("\n  ⚠ oops!\n", out);
+
+    let out = fmt_report_with_settings(MyBad.into(), |handler| {
+        handler.with_always_show_header(true)
+    });
+    println!("Error: {}", out);
+    assert_eq!("warning\n\n  ⚠ oops!\n", out);
+    Ok(())
+}
+
+#[test]
+fn help_before_snippet() -> Result<(), MietteError> {
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("oops!")]
+    #[diagnostic(code(oops::my::bad), help("try doing it better next time?"))]
+    struct MyBad {
+        #[source_code]
+        src: NamedSource<String>,
+        #[label("this bit here")]
+        highlight: SourceSpan,
+    }
+
+    let src = "source\n  text\n    here".to_string();
+    let err = MyBad {
+        src: NamedSource::new("bad_file.rs", src),
+        highlight: (9, 4).into(),
+    };
+    let out = fmt_report_with_settings(err.into(), |handler| {
+        handler
+            .without_syntax_highlighting()
+            .with_help_position(HelpPosition::BeforeSnippet)
+    });
+    println!("Error: {}", out);
+    let expected = r#"oops::my::bad
+
+  × oops!
+  help: try doing it better next time?
+   ╭─[bad_file.rs:2:3]
+ 1 │ source
+ 2 │   text
+   ·   ──┬─
+   ·     ╰── this bit here
+ 3 │     here
+   ╰────
+"#
+    .trim_start()
+    .to_string();
+    assert_eq!(expected, out);
+    Ok(())
+}
+
+#[test]
+fn severity_in_header() -> Result<(), MietteError> {
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("oops!")]
+    #[diagnostic(code(oops::my::bad))]
+    struct MyBad;
+
+    let out = fmt_report_with_settings(MyBad.into(), |handler| {
+        handler.with_severity_in_header(true)
+    });
+    println!("Error: {}", out);
+    assert_eq!("error[oops::my::bad]\n\n  × oops!\n", out);
+    Ok(())
+}
+
+#[test]
+fn framed_source() -> Result<(), MietteError> {
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("oops!")]
+    struct MyBad {
+        #[source_code]
+        src: NamedSource<FramedSource<&'static str>>,
+        #[label("this bit here")]
+        highlight: SourceSpan,
+    }
+
+    let src = FramedSource::new("let x = 1;").with_header("// generated");
+    let err = MyBad {
+        highlight: src.framed_span((4, 1)),
+        src: NamedSource::new("fragment.rs", src),
+    };
+    let out = fmt_report_with_settings(err.into(), |handler| handler.without_syntax_highlighting());
+    println!("Error: {}", out);
+    let expected = r#"
+  × oops!
+   ╭─[fragment.rs:2:5]
+ 1 │ // generated
+ 2 │ let x = 1;
+   ·     ┬
+   ·     ╰── this bit here
+   ╰────
+"#;
+    assert_eq!(expected, out);
+    Ok(())
+}
+
+#[test]
+fn show_missing_source() -> Result<(), MietteError> {
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("oops!")]
+    struct MyBad {
+        #[label("this bit here")]
+        highlight1: SourceSpan,
+        #[label]
+        highlight2: SourceSpan,
+    }
+
+    let err = MyBad {
+        highlight1: (9, 4).into(),
+        highlight2: (20, 2).into(),
+    };
+    let out =
+        fmt_report_with_settings(err.into(), |handler| handler.with_show_missing_source(true));
+    println!("Error: {}", out);
+    let expected = r#"
+  × oops!
+  [no source available for label 'this bit here' at 9..13]
+  [no source available for label at 20..22]
+"#;
+    assert_eq!(expected, out);
+    Ok(())
+}
+
+#[test]
+fn show_columns() -> Result<(), MietteError> {
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("oops!")]
+    struct MyBad {
+        #[source_code]
+        src: NamedSource<String>,
+        #[label("this bit here")]
+        highlight: SourceSpan,
+    }
+
+    let err = MyBad {
+        src: NamedSource::new("bad_file.rs", "source\n  text\n    here".to_string()),
+        highlight: (9, 4).into(),
+    };
+    let out = fmt_report_with_settings(err.into(), |handler| {
+        handler
+            .without_syntax_highlighting()
+            .with_show_columns(true)
+    });
+    println!("Error: {}", out);
+    let expected = r#"
+  × oops!
+     ╭─[bad_file.rs:2:3]
+   1 │ source
+ 2:3 │   text
+     ·   ──┬─
+     ·     ╰── this bit here
+   3 │     here
+     ╰────
+"#;
+    assert_eq!(expected, out);
+    Ok(())
+}
+
+#[test]
+fn text_severity() -> Result<(), MietteError> {
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("oops!")]
+    #[diagnostic(code(oops::my::bad))]
+    struct MyBad;
+
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("careful!")]
+    #[diagnostic(severity(Warning))]
+    struct Careful;
+
+    let out = fmt_report_with_settings(MyBad.into(), |handler| handler.with_text_severity(true));
+    println!("Error: {}", out);
+    assert_eq!("oops::my::bad\n\n  error: oops!\n", out);
+
+    let out = fmt_report_with_settings(Careful.into(), |handler| handler.with_text_severity(true));
+    println!("Error: {}", out);
+    assert_eq!("\n  warning: careful!\n", out);
+    Ok(())
+}
+
+#[test]
+fn zero_context_lines() -> Result<(), MietteError> {
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("oops!")]
+    struct MyBad {
+        #[source_code]
+        src: NamedSource<String>,
+        #[label("this bit here")]
+        highlight: SourceSpan,
+    }
+
+    let err = MyBad {
+        src: NamedSource::new("bad_file.rs", "source\n  text here\n    more".to_string()),
+        highlight: (14, 4).into(),
+    };
+    let out = fmt_report_with_settings(err.into(), |handler| {
+        handler.without_syntax_highlighting().with_context_lines(0)
+    });
+    println!("Error: {}", out);
+    let expected = r#"
+  × oops!
+   ╭─[bad_file.rs:2:8]
+ 2 │   text here
+   ·        ──┬─
+   ·          ╰── this bit here
+   ╰────
+"#;
+    assert_eq!(expected, out);
+    Ok(())
+}
+
+#[test]
+fn inline_label_threshold() -> Result<(), MietteError> {
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("oops!")]
+    struct MyBad {
+        #[source_code]
+        src: NamedSource<String>,
+        #[label(collection)]
+        highlights: Vec<LabeledSpan>,
+    }
+
+    let err = MyBad {
+        src: NamedSource::new("bad_file.rs", "let (a, b, c, d, e) = f();".to_string()),
+        highlights: ["a", "b", "c", "d", "e"]
+            .iter()
+            .enumerate()
+            .map(|(i, name)| LabeledSpan::at(5 + 3 * i..6 + 3 * i, format!("`{}` unused", name)))
+            .collect(),
+    };
+    let out = fmt_report_with_settings(err.into(), |handler| {
+        handler
+            .without_syntax_highlighting()
+            .with_inline_label_threshold(3)
+    });
+    println!("Error: {}", out);
+    let expected = r#"
+  × oops!
+   ╭─[bad_file.rs:1:6]
+ 1 │ let (a, b, c, d, e) = f();
+   ·      1  2  3  4  5
+   · [1] `a` unused
+   · [2] `b` unused
+   · [3] `c` unused
+   · [4] `d` unused
+   · [5] `e` unused
+   ╰────
+"#;
+    assert_eq!(expected, out);
+    Ok(())
+}
+
+#[test]
+fn inline_label_threshold_many_labels() -> Result<(), MietteError> {
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("oops!")]
+    struct MyBad {
+        #[source_code]
+        src: NamedSource<String>,
+        #[label(collection)]
+        highlights: Vec<LabeledSpan>,
+    }
+
+    let names = ["a", "b", "c", "d", "e", "f", "g", "h", "i", "jj", "kkk"];
+    let err = MyBad {
+        src: NamedSource::new(
+            "bad_file.rs",
+            "let (a, b, c, d, e, f, g, h, i, jj, kkk) = f();".to_string(),
+        ),
+        highlights: [5, 8, 11, 14, 17, 20, 23, 26, 29, 32, 36]
+            .iter()
+            .zip(names)
+            .map(|(&start, name)| {
+                LabeledSpan::at(start..start + name.len(), format!("`{}` unused", name))
+            })
+            .collect(),
+    };
+    let out = fmt_report_with_settings(err.into(), |handler| {
+        handler
+            .without_syntax_highlighting()
+            .with_inline_label_threshold(3)
+    });
+    println!("Error: {}", out);
+    let expected = r#"
+  × oops!
+   ╭─[bad_file.rs:1:6]
+ 1 │ let (a, b, c, d, e, f, g, h, i, jj, kkk) = f();
+   ·      1  2  3  4  5  6  7  8  9  10  11─
+   · [1] `a` unused
+   · [2] `b` unused
+   · [3] `c` unused
+   · [4] `d` unused
+   · [5] `e` unused
+   · [6] `f` unused
+   · [7] `g` unused
+   · [8] `h` unused
+   · [9] `i` unused
+   · [10] `jj` unused
+   · [11] `kkk` unused
+   ╰────
+"#;
+    assert_eq!(expected, out);
+    Ok(())
+}
+
+#[test]
+fn diff_view() -> Result<(), MietteError> {
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("oops!")]
+    #[diagnostic(code(oops::my::bad))]
+    struct MyBad {
+        #[source_code]
+        src: DiffSource,
+        #[label("was this")]
+        before: SourceSpan,
+        #[label("now this")]
+        after: SourceSpan,
+    }
+
+    let src = DiffSource::new("let x = 1;\nx + 1\n", "let x = 2;\nx + 1\n");
+    let err = MyBad {
+        before: (8, 1).into(),
+        after: src.after_span((8, 1)),
+        src,
+    };
+    let out = fmt_report_with_settings(err.into(), |handler| handler.with_diff_view(true));
+    println!("Error: {}", out);
+    let expected = r#"oops::my::bad
+
+  × oops!
+   ╭─[1:9]                    ╭─[1:9]
+ 1 │ let x = 1;             1 │ let x = 2;
+   ·         ┬                ·         ┬
+   ·         ╰── was this     ·         ╰── now this
+ 2 │ x + 1                  2 │ x + 1
+   ╰────                      ╰────
+"#
+    .trim_start()
+    .to_string();
+    assert_eq!(expected, out);
+    Ok(())
+}
+
+#[test]
+fn diff_view_label_across_split() -> Result<(), MietteError> {
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("oops!")]
+    #[diagnostic(code(oops::my::bad))]
+    struct MyBad {
+        #[source_code]
+        src: DiffSource,
+        #[label("moved")]
+        moved: SourceSpan,
+    }
+
+    let src = DiffSource::new("let x = 1;", "let y = 1;");
+    let err = MyBad {
+        moved: (8, 6).into(),
+        src,
+    };
+    let out = fmt_report_with_settings(err.into(), |handler| {
+        handler.with_diff_view(true).without_syntax_highlighting()
+    });
+    println!("Error: {}", out);
+    let expected = r#"oops::my::bad
+
+  × oops!
+   ╭────                    ╭────
+ 1 │ let x = 1;           1 │ let y = 1;
+   ·         ─┬             · ──┬─
+   ·          ╰── moved     ·   ╰── moved
+   ╰────                    ╰────
+"#
+    .trim_start()
+    .to_string();
+    assert_eq!(expected, out);
+    Ok(())
+}
+
+#[test]
+fn context_snippets() -> Result<(), MietteError> {
+    #[derive(Debug, Error)]
+    #[error("oops!")]
+    struct MyBad {
+        src: NamedSource<String>,
+        highlight: SourceSpan,
+        declaration: SourceSpan,
+    }
+
+    impl Diagnostic for MyBad {
+        fn source_code(&self) -> Option<&dyn miette::SourceCode> {
+            Some(&self.src)
+        }
+
+        fn labels(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+            Some(Box::new(std::iter::once(LabeledSpan::at(
+                self.highlight,
+                "used here",
+            ))))
+        }
+
+        fn context_snippets(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+            Some(Box::new(std::iter::once(LabeledSpan::at(
+                self.declaration,
+                "for reference, the declaration is here:",
+            ))))
+        }
+    }
+
+    let src = "let x = 1;\n\n\n\nfoo(x);\n".to_string();
+    let err = MyBad {
+        src: NamedSource::new("bad_file.rs", src),
+        highlight: (18, 1).into(),
+        declaration: (4, 1).into(),
+    };
+    let out = fmt_report(err.into());
+    println!("Error: {}", out);
+    let expected = r#"
+  × oops!
+   ╭─[bad_file.rs:5:5]
+ 4 │ 
+ 5 │ foo(x);
+   ·     ┬
+   ·     ╰── used here
+   ╰────
+  for reference, the declaration is here:
+   ╭─[bad_file.rs:1:1]
+ 1 │ let x = 1;
+ 2 │ 
+   ╰────
+"#;
+    assert_eq!(expected, out);
+    Ok(())
+}
+
+#[test]
+fn render_label_context() -> Result<(), MietteError> {
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("oops!")]
+    struct MyBad {
+        #[source_code]
+        src: NamedSource<String>,
+        #[label("this bit here")]
+        highlight1: SourceSpan,
+        #[label("and here")]
+        highlight2: SourceSpan,
+        #[label("and here too")]
+        highlight3: SourceSpan,
+    }
+
+    let src = "source\n  text\n    here\n\n\n\nmore\n".to_string();
+    let err = MyBad {
+        src: NamedSource::new("bad_file.rs", src),
+        highlight1: (0, 6).into(),
+        highlight2: (9, 4).into(),
+        highlight3: (24, 4).into(),
+    };
+    let handler = GraphicalReportHandler::new_themed(GraphicalTheme::unicode_nocolor())
+        .without_syntax_highlighting();
+    let mut out = String::new();
+    handler
+        .render_label_context(&mut out, &err, 1)
+        .map_err(|_| MietteError::OutOfBounds)?;
+    println!("Error: {}", out);
+    let expected = r#"   ╭─[bad_file.rs:2:3]
+ 1 │ source
+ 2 │   text
+   ·   ──┬─
+   ·     ╰── and here
+ 3 │     here
+   ╰────
+"#;
+    assert_eq!(expected, out);
+    assert!(handler
+        .render_label_context(&mut String::new(), &err, 3)
+        .is_err());
+    Ok(())
+}
+
+#[test]
+fn without_render_snippets() -> Result<(), MietteError> {
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("oops!")]
+    #[diagnostic(code(oops::my::bad), help("try doing it better next time?"))]
+    struct MyBad {
+        #[source_code]
+        src: NamedSource<String>,
+        #[label("this bit here")]
+        highlight: SourceSpan,
+    }
+
+    let src = "source\n  text\n    here".to_string();
+    let err = MyBad {
+        src: NamedSource::new("bad_file.rs", src),
+        highlight: (9, 4).into(),
+    };
+    let out = fmt_report_with_settings(err.into(), |handler| handler.with_render_snippets(false));
+    println!("Error: {}", out);
+    let expected = r#"oops::my::bad
+
+  × oops!
+  help: try doing it better next time?
+"#;
+    assert_eq!(expected, out);
+    Ok(())
+}
+
+#[test]
+fn snippet_caption() -> Result<(), MietteError> {
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("oops!")]
+    #[diagnostic(code(oops::my::bad))]
+    struct MyBad {
+        #[source_code]
+        src: NamedSource<String>,
+        #[label("this bit here")]
+        highlight: SourceSpan,
+    }
+
+    let src = "source\n  text\n    here".to_string();
+    let err = MyBad {
+        src: NamedSource::new("bad_file.rs", src),
+        highlight: (9, 4).into(),
+    };
+    let out = fmt_report_with_settings(err.into(), |handler| {
+        handler
+            .without_syntax_highlighting()
+            .with_render_snippet_caption(true)
+    });
+    println!("Error: {}", out);
+    let expected = r#"oops::my::bad
+
+  × oops!
+   ╭─[bad_file.rs:2:3] this bit here
+ 1 │ source
+ 2 │   text
+   ·   ──┬─
+   ·     ╰── this bit here
+ 3 │     here
+   ╰────
+"#;
+    assert_eq!(expected, out);
+    Ok(())
+}
+
+#[test]
+fn indent() -> Result<(), MietteError> {
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("oops!")]
+    #[diagnostic(code(oops::my::bad), help("try doing it better next time?"))]
+    struct MyBad {
+        #[source_code]
+        src: NamedSource<String>,
+        #[label("this bit here")]
+        highlight: SourceSpan,
+    }
+
+    let src = "source\n  text\n    here".to_string();
+    let err = MyBad {
+        src: NamedSource::new("bad_file.rs", src),
+        highlight: (9, 4).into(),
+    };
+    let out = fmt_report_with_settings(err.into(), |handler| {
+        handler.without_syntax_highlighting().with_indent(4)
+    });
+    println!("Error: {}", out);
+    assert!(out
+        .lines()
+        .filter(|line| !line.is_empty())
+        .all(|line| line.starts_with("    ")));
+    let expected = r#"    oops::my::bad
+
+      × oops!
+       ╭─[bad_file.rs:2:3]
+     1 │ source
+     2 │   text
+       ·   ──┬─
+       ·     ╰── this bit here
+     3 │     here
+       ╰────
+      help: try doing it better next time?
+"#;
+    assert_eq!(expected, out);
+    Ok(())
+}
+
+#[test]
+fn tab_width_from_source() -> Result<(), MietteError> {
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("oops!")]
+    #[diagnostic(code(oops::my::bad))]
+    struct MyBad {
+        #[source_code]
+        src: NamedSource<String>,
+        #[label("this bit here")]
+        highlight: SourceSpan,
+    }
+
+    let src = "source\n\t\ttext\n    here".to_string();
+    let err = MyBad {
+        src: NamedSource::new("bad_file.rs", src).with_tab_width(2),
+        highlight: (9, 4).into(),
+    };
+    // Not `fmt_report`, which sets a tab width when `REPLACE_TABS` is set.
+    let out = fmt_report_with_settings(err.into(), |handler| handler.without_syntax_highlighting());
+    println!("Error: {}", out);
+    let expected = r#"oops::my::bad
+
+  × oops!
+   ╭─[bad_file.rs:2:3]
+ 1 │ source
+ 2 │     text
+   ·     ──┬─
+   ·       ╰── this bit here
+ 3 │     here
+   ╰────
+"#;
+    assert_eq!(expected, out);
+    Ok(())
+}
+
+#[test]
+fn named_source_with_name() -> Result<(), MietteError> {
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("oops!")]
+    #[diagnostic(code(oops::my::bad))]
+    struct MyBad {
+        #[source_code]
+        src: NamedSource<String>,
+        #[label("this bit here")]
+        highlight: SourceSpan,
+    }
+
+    let src = "source\n  text\n    here".to_string();
+    let err = MyBad {
+        src: NamedSource::new("/home/me/project/bad_file.rs", src).with_name("bad_file.rs"),
+        highlight: (9, 4).into(),
+    };
+    let out = fmt_report(err.into());
+    println!("Error: {}", out);
+    let expected = r#"oops::my::bad
+
+  × oops!
+   ╭─[bad_file.rs:2:3]
+ 1 │ source
+ 2 │   text
+   ·   ──┬─
+   ·     ╰── this bit here
+ 3 │     here
+   ╰────
+"#;
+    assert_eq!(expected, out);
+    Ok(())
+}
+
+#[test]
+fn long_message() -> Result<(), MietteError> {
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("bad config")]
+    #[diagnostic(
+        code(oops::my::bad),
+        long_message("the configuration file at {path} couldn't be parsed"),
+        help("try doing it better next time?")
+    )]
+    struct MyBad {
+        path: String,
+        #[source_code]
+        src: NamedSource<String>,
+        #[label("this bit here")]
+        highlight: SourceSpan,
+    }
+
+    let src = "source\n  text\n    here".to_string();
+    let err = MyBad {
+        path: "foo.toml".into(),
+        src: NamedSource::new("foo.toml", src),
+        highlight: (9, 4).into(),
+    };
+    let out = fmt_report(err.into());
+    println!("Error: {}", out);
+    let expected = r#"oops::my::bad
+
+  × bad config
+    the configuration file at foo.toml couldn't be parsed
+   ╭─[foo.toml:2:3]
+ 1 │ source
+ 2 │   text
+   ·   ──┬─
+   ·     ╰── this bit here
+ 3 │     here
+   ╰────
+  help: try doing it better next time?
+"#;
+    assert_eq!(expected, out);
+    Ok(())
+}
+
+#[test]
+fn default_help() -> Result<(), MietteError> {
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("oops!")]
+    #[diagnostic(severity(Advice))]
+    struct MyAdvice;
+
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("oops!")]
+    #[diagnostic(severity(Advice), help("do this instead"))]
+    struct MyAdviceWithHelp;
+
+    fn with_default_help(handler: GraphicalReportHandler) -> GraphicalReportHandler {
+        handler.with_default_help(
+            Severity::Advice,
+            "This is a suggestion; no action required.",
+        )
+    }
+
+    let out = fmt_report_with_settings(MyAdvice.into(), with_default_help);
+    assert_eq!(
+        "\n  ☞ oops!\n  help: This is a suggestion; no action required.\n",
+        out
+    );
+    let out = fmt_report_with_settings(MyAdviceWithHelp.into(), with_default_help);
+    assert_eq!("\n  ☞ oops!\n  help: do this instead\n", out);
+    Ok(())
+}
+
+#[test]
+fn show_whitespace() -> Result<(), MietteError> {
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("oops!")]
+    #[diagnostic(code(oops::my::bad))]
+    struct MyBad {
+        #[source_code]
+        src: NamedSource<String>,
+        #[label("trailing whitespace")]
+        highlight: SourceSpan,
+    }
+
+    let src = "let x = 1;  \t\nlet y = 2;".to_string();
+    let err = MyBad {
+        src: NamedSource::new("bad_file.rs", src),
+        highlight: (10, 3).into(),
+    };
+    let out = fmt_report_with_settings(err.into(), |handler| {
+        handler
+            .without_syntax_highlighting()
+            .with_show_whitespace(true)
+    });
+    println!("Error: {}", out);
+    let expected = r#"oops::my::bad
+
+  × oops!
+   ╭─[bad_file.rs:1:11]
+ 1 │ let x = 1;··→   
+   ·           ───┬──
+   ·              ╰── trailing whitespace
+ 2 │ let y = 2;
+   ╰────
+"#;
+    assert_eq!(expected, out);
+    Ok(())
+}
+
+#[test]
+fn primary_label() {
+    #[derive(Error, Debug, Diagnostic)]
+    #[error("oops!")]
+    struct MyBad {
+        #[source_code]
+        src: NamedSource<&'static str>,
+        #[label]
+        first_label: SourceSpan,
+        #[label(primary, "nope")]
+        second_label: SourceSpan,
+    }
+    let err = MyBad {
+        src: NamedSource::new("issue", "this is the first line\nthis is the second line"),
+        first_label: (2, 4).into(),
+        second_label: (24, 4).into(),
+    };
+    let out = fmt_report(err.into());
+    println!("Error: {}", out);
+
+    // line 2 should be the primary, not line 1
+    let expected = r#"
+  × oops!
+   ╭─[issue:2:2]
+ 1 │ this is the first line
+   ·   ────
+ 2 │ this is the second line
+   ·  ──┬─
+   ·    ╰── nope
+   ╰────
+"#
+    .to_string();
+
+    assert_eq!(expected, out);
+}
+
+#[test]
+fn single_line_with_wide_char_unaligned_span_start() -> Result<(), MietteError> {
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("oops!")]
+    #[diagnostic(code(oops::my::bad), help("try doing it better next time?"))]
+    struct MyBad {
+        #[source_code]
+        src: NamedSource<String>,
+        #[label("this bit here")]
+        highlight: SourceSpan,
+    }
+
+    let src = "source\n  👼🏼text\n    here".to_string();
+    let err = MyBad {
+        src: NamedSource::new("bad_file.rs", src),
+        highlight: (10, 5).into(),
+    };
+    let out = fmt_report(err.into());
+    println!("Error: {}", out);
+    let expected = r#"oops::my::bad
+
+  × oops!
+   ╭─[bad_file.rs:2:4]
+ 1 │ source
+ 2 │   👼🏼text
+   ·   ──┬─
+   ·     ╰── this bit here
+ 3 │     here
+   ╰────
+  help: try doing it better next time?
+"#
+    .trim_start()
+    .to_string();
+    assert_eq!(expected, out);
+    Ok(())
+}
+
+#[test]
+fn single_line_with_wide_char_unaligned_span_end() -> Result<(), MietteError> {
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("oops!")]
+    #[diagnostic(code(oops::my::bad), help("try doing it better next time?"))]
+    struct MyBad {
+        #[source_code]
+        src: NamedSource<String>,
+        #[label("this bit here")]
+        highlight: SourceSpan,
+    }
+
+    let src = "source\n  text 👼🏼\n    here".to_string();
+    let err = MyBad {
+        src: NamedSource::new("bad_file.rs", src),
+        highlight: (9, 6).into(),
+    };
+    let out = fmt_report(err.into());
+    println!("Error: {}", out);
+    let expected = r#"oops::my::bad
+
+  × oops!
+   ╭─[bad_file.rs:2:3]
+ 1 │ source
+ 2 │   text 👼🏼
+   ·   ───┬───
+   ·      ╰── this bit here
+ 3 │     here
+   ╰────
+  help: try doing it better next time?
+"#
+    .trim_start()
+    .to_string();
+    assert_eq!(expected, out);
+    Ok(())
+}
+
+#[test]
+fn single_line_with_wide_char_unaligned_span_empty() -> Result<(), MietteError> {
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("oops!")]
+    #[diagnostic(code(oops::my::bad), help("try doing it better next time?"))]
+    struct MyBad {
+        #[source_code]
+        src: NamedSource<String>,
+        #[label("this bit here")]
+        highlight: SourceSpan,
+    }
+
+    let src = "source\n  👼🏼text\n    here".to_string();
+    let err = MyBad {
+        src: NamedSource::new("bad_file.rs", src),
+        highlight: (10, 0).into(),
+    };
+    let out = fmt_report(err.into());
+    println!("Error: {}", out);
+    let expected = r#"oops::my::bad
+
+  × oops!
+   ╭─[bad_file.rs:2:4]
+ 1 │ source
+ 2 │   👼🏼text
+   ·   ▲
+   ·   ╰── this bit here
+ 3 │     here
+   ╰────
+  help: try doing it better next time?
+"#
+    .trim_start()
+    .to_string();
+    assert_eq!(expected, out);
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "syntect-highlighter")]
+fn syntax_highlighter() {
+    std::env::set_var("REPLACE_TABS", "4");
+    #[derive(Debug, Error, Diagnostic)]
+    #[error("This is an error")]
     #[diagnostic()]
     pub struct Test {
         #[source_code]
@@ -2124,6 +4504,111 @@ fn non_adjacent_highlight() -> Result<(), MietteError> {
     Ok(())
 }
 
+#[test]
+fn context_merge_gap() -> Result<(), MietteError> {
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("oops!")]
+    struct MyBad {
+        #[source_code]
+        src: NamedSource<String>,
+        #[label = "this bit here"]
+        highlight1: SourceSpan,
+        #[label = "also this bit"]
+        highlight2: SourceSpan,
+    }
+
+    let my_bad = || MyBad {
+        src: NamedSource::new("bad_file.rs", "one\ntwo\nthree\nfour".to_string()),
+        highlight1: (0, 3).into(),
+        highlight2: (14, 4).into(),
+    };
+
+    let out = fmt_report_with_settings(my_bad().into(), |handler| {
+        handler
+            .without_syntax_highlighting()
+            .with_context_lines(0)
+            .with_context_merge_gap(3)
+    });
+    println!("Error: {}", out);
+    let expected = "
+  × oops!
+   ╭─[bad_file.rs:1:1]
+ 1 │ one
+   · ─┬─
+   ·  ╰── this bit here
+ 2 │ two
+ 3 │ three
+ 4 │ four
+   · ──┬─
+   ·   ╰── also this bit
+   ╰────
+";
+    assert_eq!(expected, &out);
+
+    let out = fmt_report_with_settings(my_bad().into(), |handler| {
+        handler
+            .without_syntax_highlighting()
+            .with_context_lines(0)
+            .with_context_merge_gap(1)
+    });
+    println!("Error: {}", out);
+    let expected = "
+  × oops!
+   ╭─[bad_file.rs:1:1]
+ 1 │ one
+   · ─┬─
+   ·  ╰── this bit here
+   ╰────
+   ╭─[bad_file.rs:4:1]
+ 4 │ four
+   · ──┬─
+   ·   ╰── also this bit
+   ╰────
+";
+    assert_eq!(expected, &out);
+    Ok(())
+}
+
+#[test]
+fn classic_layout() -> Result<(), MietteError> {
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("oops!")]
+    #[diagnostic(code(oops::my::bad), help("try doing it better next time?"))]
+    struct MyBad {
+        #[source_code]
+        src: NamedSource<String>,
+        #[label("this bit here")]
+        highlight: SourceSpan,
+        #[label]
+        unlabeled: SourceSpan,
+    }
+
+    let src = "source\n  text\n    here".to_string();
+    let err = MyBad {
+        src: NamedSource::new("bad_file.rs", src),
+        highlight: (9, 4).into(),
+        unlabeled: (18, 4).into(),
+    };
+    let out = fmt_report_with_settings(err.into(), |handler| {
+        handler.without_syntax_highlighting().with_classic(true)
+    });
+    let expected = r#"oops::my::bad
+
+  × oops!
+bad_file.rs:2:3: this bit here
+  text
+  ^^^^
+bad_file.rs:3:5: oops!
+    here
+    ^^^^
+  help: try doing it better next time?
+"#
+    .trim_start()
+    .to_string();
+    assert_eq!(expected, out);
+    Ok(())
+}
+
 #[test]
 fn invalid_span_bad_offset() -> Result<(), MietteError> {
     #[derive(Debug, Diagnostic, Error)]
@@ -1,6 +1,10 @@
 #![cfg(feature = "fancy-no-backtrace")]
 
-use miette::{Diagnostic, MietteError, NamedSource, NarratableReportHandler, Report, SourceSpan};
+use miette::{
+    Diagnostic, MietteError, NamedSource, NarratableReportHandler, NarrativeLabel,
+    NarrativeLabelAttach, NarrativeLine, NarrativeReport, NarrativeSnippet, Report, Severity,
+    SourceSpan,
+};
 
 use miette::{GraphicalReportHandler, GraphicalTheme};
 
@@ -94,6 +98,83 @@ diagnostic code: oops::my::bad
     Ok(())
 }
 
+#[test]
+fn compute_structured_report() -> Result<(), MietteError> {
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("oops!")]
+    #[diagnostic(code(oops::my::bad), help("try doing it better next time?"))]
+    struct MyBad {
+        #[source_code]
+        src: NamedSource<String>,
+        #[label("this bit here")]
+        highlight: SourceSpan,
+    }
+
+    let src = "source\n  text\n    here".to_string();
+    let err = MyBad {
+        src: NamedSource::new("bad_file.rs", src),
+        highlight: (9, 4).into(),
+    };
+    let report = NarratableReportHandler::new().compute(&err).unwrap();
+    let line = |line_number, text: &str, labels| NarrativeLine {
+        line_number,
+        text: text.into(),
+        labels,
+    };
+    let expected = NarrativeReport {
+        title: "oops!".into(),
+        severity: Severity::Error,
+        causes: vec![],
+        snippets: vec![NarrativeSnippet {
+            name: Some("bad_file.rs".into()),
+            line: 1,
+            column: 1,
+            lines: vec![
+                line(1, "source", vec![]),
+                line(
+                    2,
+                    "  text",
+                    vec![NarrativeLabel {
+                        attach: NarrativeLabelAttach::Contained {
+                            col_start: 3,
+                            col_end: 6,
+                        },
+                        label: Some("this bit here".into()),
+                    }],
+                ),
+                line(3, "    here", vec![]),
+            ],
+        }],
+        help: Some("try doing it better next time?".into()),
+        code: Some("oops::my::bad".into()),
+        url: None,
+        related: None,
+    };
+    assert_eq!(expected, report);
+    Ok(())
+}
+
+#[test]
+fn out_of_bounds_label_is_an_error() {
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("oops!")]
+    struct MyBad {
+        #[source_code]
+        src: NamedSource<String>,
+        #[label("this bit here")]
+        highlight: SourceSpan,
+    }
+
+    let err = MyBad {
+        src: NamedSource::new("bad_file.rs", "source\n  text".to_string()),
+        highlight: (50, 4).into(),
+    };
+    let handler = NarratableReportHandler::new();
+    assert!(handler.compute(&err).is_err());
+    let mut out = String::new();
+    assert!(handler.render_report(&mut out, &err).is_err());
+}
+
 #[test]
 fn single_line_highlight_offset_zero() -> Result<(), MietteError> {
     #[derive(Debug, Diagnostic, Error)]
@@ -607,6 +688,55 @@ diagnostic code: oops::my::bad
     Ok(())
 }
 
+#[test]
+fn nested_related() -> Result<(), MietteError> {
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("{name}")]
+    struct Node {
+        name: &'static str,
+        #[related]
+        related: Vec<Node>,
+    }
+
+    let err = Node {
+        name: "root",
+        related: vec![
+            Node {
+                name: "child",
+                related: vec![Node {
+                    name: "grandchild",
+                    related: vec![],
+                }],
+            },
+            Node {
+                name: "sibling",
+                related: vec![],
+            },
+        ],
+    };
+    let out = fmt_report(err.into());
+    println!("Error: {}", out);
+    let expected = r#"root
+    Diagnostic severity: error
+
+Error: child
+    Diagnostic severity: error
+
+
+Additionally: Error: grandchild
+    Diagnostic severity: error
+
+
+Error: sibling
+    Diagnostic severity: error
+
+
+"#
+    .to_string();
+    assert_eq!(expected, out);
+    Ok(())
+}
+
 #[test]
 fn related_source_code_propagation() -> Result<(), MietteError> {
     #[derive(Debug, Diagnostic, Error)]
@@ -0,0 +1,27 @@
+#![cfg(all(feature = "anyhow", feature = "fancy-no-backtrace"))]
+
+use anyhow::Context;
+use miette::{AnyhowDiagnostic, GraphicalReportHandler, GraphicalTheme, Report};
+
+#[test]
+fn anyhow_causes() {
+    let error = Err::<(), _>(anyhow::anyhow!("file not found"))
+        .context("failed to read config")
+        .context("failed to start")
+        .unwrap_err();
+    let report = Report::new(AnyhowDiagnostic::from(error));
+    assert_eq!(report.to_string(), "failed to start");
+    assert_eq!(report.chain().count(), 3);
+
+    let mut out = String::new();
+    GraphicalReportHandler::new_themed(GraphicalTheme::unicode_nocolor())
+        .with_width(80)
+        .render_report(&mut out, report.as_ref())
+        .unwrap();
+    let expected = r#"
+  × failed to start
+  ├─▶ failed to read config
+  ╰─▶ file not found
+"#;
+    assert_eq!(expected, out);
+}
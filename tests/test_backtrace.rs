@@ -0,0 +1,18 @@
+use miette::{miette, Report};
+
+#[test]
+fn test_backtrace() {
+    // Backtraces are captured (or not) based on the environment, which std
+    // only checks once per process.
+    std::env::set_var("RUST_LIB_BACKTRACE", "1");
+
+    let report: Report = miette!("oh no!");
+    assert!(report.backtrace().is_some());
+
+    let original = report.backtrace().unwrap().to_string();
+    let wrapped = report.wrap_err("while testing");
+    assert_eq!(wrapped.backtrace().unwrap().to_string(), original);
+
+    let wrapped = wrapped.context_with_source("while testing more", "source");
+    assert_eq!(wrapped.backtrace().unwrap().to_string(), original);
+}
@@ -244,3 +244,26 @@ fn test_boxed_sources() {
             .to_string()
     );
 }
+
+#[test]
+fn test_map_diagnostic() {
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("[redacted]")]
+    #[diagnostic(code(secret::code))]
+    struct Redacted;
+
+    let report = miette!(code = "secret::code", "password is hunter2");
+    let report = report.map_diagnostic(|diag| {
+        assert_eq!(diag.to_string(), "password is hunter2");
+        Box::new(Redacted)
+    });
+
+    let mut out = String::new();
+    miette::NarratableReportHandler::new()
+        .render_report(&mut out, report.as_ref())
+        .unwrap();
+    assert_eq!(
+        out,
+        "[redacted]\n    Diagnostic severity: error\ndiagnostic code: secret::code\n"
+    );
+}
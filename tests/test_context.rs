@@ -159,3 +159,31 @@ fn test_unsuccessful_downcast() {
     drop(err);
     assert!(dropped.all());
 }
+
+#[test]
+fn test_extensions_survive_wrapping() {
+    #[derive(Debug, PartialEq)]
+    struct RequestId(u64);
+
+    let mut err = Report::msg("oh no!");
+    assert_eq!(err.get::<RequestId>(), None);
+    assert_eq!(err.insert(RequestId(1)), None);
+    assert_eq!(err.insert(RequestId(42)), Some(RequestId(1)));
+    err.insert("some extra context");
+
+    let mut err: Report = Err::<(), _>(err.wrap_err("low"))
+        .wrap_err("high")
+        .unwrap_err();
+    assert_eq!(err.get::<RequestId>(), Some(&RequestId(42)));
+    assert_eq!(err.get::<&str>(), Some(&"some extra context"));
+
+    err.get_mut::<RequestId>().unwrap().0 += 1;
+    assert_eq!(err.get::<RequestId>(), Some(&RequestId(43)));
+
+    let err = err.with_source_code("source").map_labels(|label| label);
+    assert_eq!(err.get::<RequestId>(), Some(&RequestId(43)));
+    assert_eq!(err.get::<&str>(), Some(&"some extra context"));
+
+    let err = err.context_with_source("higher", "source");
+    assert_eq!(err.get::<RequestId>(), Some(&RequestId(43)));
+}
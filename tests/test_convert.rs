@@ -1,7 +1,7 @@
 mod drop;
 
 use self::drop::{DetectDrop, Flag};
-use miette::{Diagnostic, Report, Result};
+use miette::{Diagnostic, IntoDiagnostic, Report, Result};
 
 #[test]
 fn test_convert() {
@@ -40,3 +40,21 @@ fn test_question_mark_stderr() -> Result<(), Box<dyn std::error::Error>> {
     f()?;
     Ok(())
 }
+
+#[test]
+fn test_boxed_stderr_into_diagnostic() {
+    fn f() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        Err("oh no!".into())
+    }
+    let error: Report = f().into_diagnostic().unwrap_err();
+    assert_eq!("oh no!", error.to_string());
+}
+
+#[test]
+fn test_report_from_boxed_diagnostic() {
+    let boxed: Box<dyn Diagnostic + Send + Sync> =
+        Box::new(miette::MietteDiagnostic::new("oh no!").with_code("oops"));
+    let error = Report::new_boxed(boxed);
+    assert_eq!("oh no!", error.to_string());
+    assert_eq!("oops", error.code().unwrap().to_string());
+}
@@ -92,3 +92,15 @@ fn test_altdebug() {
     assert_eq!(EXPECTED_ALTDEBUG_G, format!("{:#?}", g().unwrap_err()));
     assert_eq!(EXPECTED_ALTDEBUG_H, format!("{:#?}", h().unwrap_err()));
 }
+
+#[test]
+fn test_write_to() {
+    let report = h().unwrap_err();
+    let mut out = Vec::new();
+    report.write_to(&mut out).unwrap();
+    assert_eq!(String::from_utf8(out).unwrap(), format!("{:?}\n", report));
+    // Only checks that printing doesn't fail; the output itself goes to the
+    // test harness.
+    report.eprint().unwrap();
+    report.print().unwrap();
+}
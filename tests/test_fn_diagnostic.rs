@@ -0,0 +1,25 @@
+use miette::{FnDiagnostic, LabeledSpan, NamedSource, NarratableReportHandler};
+
+#[test]
+fn renders_closures() {
+    let diag = FnDiagnostic::new(|| "oops!".to_string())
+        .with_code_fn(|| "oops::my::bad".to_string())
+        .with_labels_fn(|| vec![LabeledSpan::at(7..11, "this bit here")])
+        .with_source_code(NamedSource::new("bad_file.rs", "source\ntext\nhere"));
+
+    let mut out = String::new();
+    NarratableReportHandler::new()
+        .render_report(&mut out, &diag)
+        .unwrap();
+    let expected = r#"oops!
+    Diagnostic severity: error
+Begin snippet for bad_file.rs starting at line 1, column 1
+
+snippet line 1: source
+snippet line 2: text
+    label at line 2, columns 1 to 4: this bit here
+snippet line 3: here
+diagnostic code: oops::my::bad
+"#;
+    assert_eq!(expected, out);
+}
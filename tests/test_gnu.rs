@@ -0,0 +1,88 @@
+mod gnu_report_handler {
+    use miette::{Diagnostic, GnuReportHandler, MietteError, NamedSource, Report, SourceSpan};
+
+    use thiserror::Error;
+
+    fn fmt_report(diag: Report) -> String {
+        let mut out = String::new();
+        GnuReportHandler::new()
+            .render_report(&mut out, diag.as_ref())
+            .unwrap();
+        out
+    }
+
+    #[test]
+    fn single_label() -> Result<(), MietteError> {
+        #[derive(Debug, Diagnostic, Error)]
+        #[error("oops!")]
+        #[diagnostic(code(oops::my::bad), help("try doing it better next time?"))]
+        struct MyBad {
+            #[source_code]
+            src: NamedSource<String>,
+            #[label("this bit here")]
+            highlight: SourceSpan,
+        }
+
+        let src = "source\n  text\n    here".to_string();
+        let err = MyBad {
+            src: NamedSource::new("bad_file.rs", src),
+            highlight: (9, 4).into(),
+        };
+        let out = fmt_report(err.into());
+        println!("Error: {}", out);
+        let expected = "bad_file.rs:2:3: error: oops!\n\
+                        bad_file.rs:2:3: note: try doing it better next time?\n";
+        assert_eq!(expected, out);
+        Ok(())
+    }
+
+    #[test]
+    fn multiple_labels_and_related() -> Result<(), MietteError> {
+        #[derive(Debug, Diagnostic, Error)]
+        #[error("oops!")]
+        #[diagnostic(severity(Warning))]
+        struct MyBad {
+            #[source_code]
+            src: NamedSource<String>,
+            #[label("this bit here")]
+            highlight1: SourceSpan,
+            #[label(primary, "also this bit")]
+            highlight2: SourceSpan,
+            #[related]
+            related: Vec<Related>,
+        }
+
+        #[derive(Debug, Diagnostic, Error)]
+        #[error("related oops!")]
+        struct Related {
+            #[label]
+            highlight: SourceSpan,
+        }
+
+        let src = "source\n  text\n    here".to_string();
+        let err = MyBad {
+            src: NamedSource::new("bad_file.rs", src),
+            highlight1: (0, 6).into(),
+            highlight2: (18, 4).into(),
+            related: vec![Related {
+                highlight: (9, 4).into(),
+            }],
+        };
+        let out = fmt_report(err.into());
+        println!("Error: {}", out);
+        let expected = "bad_file.rs:3:5: warning: oops!\n\
+                        bad_file.rs:1:1: warning: oops!\n\
+                        bad_file.rs:2:3: error: related oops!\n";
+        assert_eq!(expected, out);
+        Ok(())
+    }
+
+    #[test]
+    fn no_labels() {
+        #[derive(Debug, Diagnostic, Error)]
+        #[error("oops!")]
+        struct MyBad;
+
+        assert_eq!("error: oops!\n", fmt_report(MyBad.into()));
+    }
+}
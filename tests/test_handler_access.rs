@@ -5,3 +5,15 @@ fn test_handler() {
     let error: Report = miette!("oh no!");
     let _ = error.handler();
 }
+
+#[test]
+fn test_set_handler() {
+    use miette::{miette, JSONReportHandler, Report};
+
+    let mut error: Report = miette!("oh no!");
+    error.set_handler(Box::new(JSONReportHandler::new()));
+    assert_eq!(
+        format!("{:?}", error),
+        r#"{"message": "oh no!","severity": "error","causes": [],"labels": [],"related": []}"#
+    );
+}
@@ -32,3 +32,31 @@ fn test_ensure() {
     };
     assert!(f().is_err());
 }
+
+#[cfg(feature = "fancy-no-backtrace")]
+#[test]
+fn test_diag() {
+    let report = miette::diag!(
+        "let x = ;",
+        8..9,
+        "expected an expression",
+        help = "add a value"
+    );
+    let mut out = String::new();
+    miette::GraphicalReportHandler::new_themed(miette::GraphicalTheme::unicode_nocolor())
+        .with_width(80)
+        .render_report(&mut out, report.as_ref())
+        .unwrap();
+    println!("Error: {}", out);
+    let expected = r#"
+  × expected an expression
+   ╭────
+ 1 │ let x = ;
+   ·         ┬
+   ·         ╰── expected an expression
+   ╰────
+  help: add a value
+"#
+    .to_string();
+    assert_eq!(expected, out);
+}
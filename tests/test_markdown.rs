@@ -0,0 +1,85 @@
+mod markdown_report_handler {
+    use miette::{Diagnostic, MarkdownReportHandler, NamedSource, Report, SourceSpan};
+
+    use thiserror::Error;
+
+    fn fmt_report(diag: Report) -> String {
+        let mut out = String::new();
+        MarkdownReportHandler::new()
+            .render_report(&mut out, diag.as_ref())
+            .unwrap();
+        out
+    }
+
+    #[test]
+    fn snippet_in_fence() {
+        #[derive(Debug, Diagnostic, Error)]
+        #[error("oops! <b>bad</b> *stuff*")]
+        #[diagnostic(code(oops::my::bad), help("try doing it better next time?"))]
+        struct MyBad {
+            #[source_code]
+            src: NamedSource<String>,
+            #[label("this bit here")]
+            highlight: SourceSpan,
+            #[related]
+            related: Vec<Related>,
+        }
+
+        #[derive(Debug, Diagnostic, Error)]
+        #[error("related oops!")]
+        #[diagnostic(severity(Warning))]
+        struct Related;
+
+        let src = "source\n  text\n    here".to_string();
+        let err = MyBad {
+            src: NamedSource::new("bad_file.rs", src),
+            highlight: (9, 4).into(),
+            related: vec![Related],
+        };
+        let out = fmt_report(err.into());
+        println!("Error: {}", out);
+        let expected = r#"**error\[oops::my::bad\]**: oops! &lt;b&gt;bad&lt;/b&gt; \*stuff\*
+
+`bad_file.rs:2:3`:
+```
+1 | source
+2 |   text
+  |   ^^^^ this bit here
+3 |     here
+```
+
+- **help**: try doing it better next time?
+- **warning**: related oops!
+"#;
+        assert_eq!(expected, out);
+    }
+
+    #[test]
+    fn fence_longer_than_source_backticks() {
+        #[derive(Debug, Diagnostic, Error)]
+        #[error("oops!")]
+        struct MyBad {
+            #[source_code]
+            src: String,
+            #[label]
+            highlight: SourceSpan,
+        }
+
+        let err = MyBad {
+            src: "```\nfoo\n".to_string(),
+            highlight: (4, 3).into(),
+        };
+        let out = fmt_report(err.into());
+        println!("Error: {}", out);
+        let expected = r#"**error**: oops!
+
+`<unknown>:2:1`:
+````
+1 | ```
+2 | foo
+  | ^^^
+````
+"#;
+        assert_eq!(expected, out);
+    }
+}
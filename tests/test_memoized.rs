@@ -0,0 +1,31 @@
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use miette::{miette, Diagnostic, ReportHandler};
+
+static RENDERS: AtomicUsize = AtomicUsize::new(0);
+
+struct CountingHandler;
+
+impl ReportHandler for CountingHandler {
+    fn debug(&self, diagnostic: &dyn Diagnostic, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        RENDERS.fetch_add(1, Ordering::SeqCst);
+        write!(f, "rendered: {}", diagnostic)
+    }
+}
+
+#[test]
+fn test_memoized_renders_once() {
+    miette::set_hook(Box::new(|_| Box::new(CountingHandler))).unwrap();
+
+    let report = miette!("oh no!").memoized();
+    assert_eq!(RENDERS.load(Ordering::SeqCst), 0);
+    assert_eq!(format!("{:?}", report), "rendered: oh no!");
+    assert_eq!(format!("{:?}", report), "rendered: oh no!");
+    assert_eq!(report.to_string(), "rendered: oh no!");
+    assert_eq!(RENDERS.load(Ordering::SeqCst), 1);
+
+    let report = report.into_report();
+    assert_eq!(format!("{:?}", report), "rendered: oh no!");
+    assert_eq!(RENDERS.load(Ordering::SeqCst), 2);
+}
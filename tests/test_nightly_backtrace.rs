@@ -0,0 +1,33 @@
+#![cfg(feature = "nightly-backtrace")]
+#![feature(error_generic_member_access)]
+
+use std::backtrace::Backtrace;
+use std::error::{Error, Request};
+use std::fmt;
+
+use miette::IntoDiagnostic;
+
+#[derive(Debug)]
+struct WithBacktrace(Backtrace);
+
+impl fmt::Display for WithBacktrace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("oh no!")
+    }
+}
+
+impl Error for WithBacktrace {
+    fn provide<'a>(&'a self, request: &mut Request<'a>) {
+        request.provide_ref(&self.0);
+    }
+}
+
+#[test]
+fn into_diagnostic_keeps_provided_backtrace() {
+    let err = WithBacktrace(Backtrace::force_capture());
+    let expected = err.0.to_string();
+
+    let report = Err::<(), _>(err).into_diagnostic().unwrap_err();
+    let backtrace = report.backtrace().expect("backtrace should be provided");
+    assert_eq!(backtrace.to_string(), expected);
+}
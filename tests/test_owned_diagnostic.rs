@@ -0,0 +1,134 @@
+#![cfg(all(feature = "json", feature = "fancy-no-backtrace"))]
+
+use std::io::Write;
+
+use miette::{
+    Diagnostic, GraphicalReportHandler, GraphicalTheme, JSONReportHandler, JsonToGraphical,
+    NamedSource, OwnedDiagnostic, Report, SourceSpan,
+};
+use thiserror::Error;
+
+#[derive(Debug, Diagnostic, Error)]
+#[error("oops!")]
+#[diagnostic(code(oops::my::bad), help("try doing it better next time?"))]
+struct MyBad {
+    #[source_code]
+    src: NamedSource<String>,
+    #[label("this bit here")]
+    highlight: SourceSpan,
+    #[related]
+    related: Vec<Related>,
+}
+
+#[derive(Debug, Diagnostic, Error)]
+#[error("related thing")]
+#[diagnostic(severity(Warning))]
+struct Related {
+    #[label("and this one")]
+    highlight: SourceSpan,
+}
+
+fn render(diag: &dyn Diagnostic) -> String {
+    let mut out = String::new();
+    GraphicalReportHandler::new_themed(GraphicalTheme::unicode_nocolor())
+        .with_width(80)
+        .without_syntax_highlighting()
+        .render_report(&mut out, diag)
+        .unwrap();
+    out
+}
+
+#[test]
+fn owned_diagnostic_round_trip() {
+    let err = MyBad {
+        src: NamedSource::new("bad_file.rs", "source\n  text\n    here".to_string()),
+        highlight: (9, 4).into(),
+        related: vec![Related {
+            highlight: (0, 6).into(),
+        }],
+    };
+
+    let owned = OwnedDiagnostic::from(&err as &dyn Diagnostic);
+    let json = serde_json::to_string(&owned).unwrap();
+    let deserialized: OwnedDiagnostic = serde_json::from_str(&json).unwrap();
+    assert_eq!(owned, deserialized);
+
+    let out = render(&deserialized);
+    let expected = r#"oops::my::bad
+
+  × oops!
+   ╭─[bad_file.rs:2:3]
+ 1 │ source
+ 2 │   text
+   ·   ──┬─
+   ·     ╰── this bit here
+ 3 │     here
+   ╰────
+  help: try doing it better next time?
+
+Warning: 
+  ⚠ related thing
+   ╭─[bad_file.rs:1:1]
+ 1 │ source
+   · ───┬──
+   ·    ╰── and this one
+ 2 │   text
+   ╰────
+"#;
+    assert_eq!(expected, out);
+    assert_eq!(render(&err), out);
+}
+
+#[test]
+fn json_to_graphical() {
+    let err = MyBad {
+        src: NamedSource::new("bad_file.rs", "source\n  text\n    here".to_string()),
+        highlight: (9, 4).into(),
+        related: vec![],
+    };
+    let owned = OwnedDiagnostic::from(&err as &dyn Diagnostic);
+    let json = serde_json::to_string(&owned).unwrap();
+    let (first, second) = json.split_at(json.len() / 2);
+
+    let mut writer = JsonToGraphical::new(Vec::new()).with_handler(
+        GraphicalReportHandler::new_themed(GraphicalTheme::unicode_nocolor())
+            .with_width(80)
+            .without_syntax_highlighting(),
+    );
+    write!(writer, "build started\n{}", first).unwrap();
+    writeln!(writer, "{}", second).unwrap();
+    write!(writer, "build failed").unwrap();
+    let out = String::from_utf8(writer.finish().unwrap()).unwrap();
+
+    assert_eq!(
+        out,
+        format!("build started\n{}build failed", render(&owned))
+    );
+}
+
+#[test]
+fn report_from_json() {
+    let err = MyBad {
+        src: NamedSource::new("bad_file.rs", "source\n  text\n    here".to_string()),
+        highlight: (9, 4).into(),
+        related: vec![Related {
+            highlight: (0, 6).into(),
+        }],
+    };
+    let mut json = String::new();
+    JSONReportHandler::new()
+        .render_report(&mut json, &err)
+        .unwrap();
+
+    let report = Report::from_json(&json).unwrap();
+    let out = render(report.as_ref());
+    let expected = r#"oops::my::bad
+
+  × oops!
+  help: try doing it better next time?
+
+Warning: 
+  ⚠ related thing
+"#;
+    assert_eq!(expected, out);
+}
@@ -0,0 +1,109 @@
+#![cfg(feature = "fancy-no-backtrace")]
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use miette::{Diagnostic, GraphicalReportHandler, GraphicalTheme};
+use thiserror::Error;
+
+/// Allocator that keeps track of the number of live bytes, and the highest
+/// that number has been since the last reset.
+struct ProbeAlloc;
+
+static LIVE: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for ProbeAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            let live = LIVE.fetch_add(layout.size(), Ordering::SeqCst) + layout.size();
+            PEAK.fetch_max(live, Ordering::SeqCst);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        LIVE.fetch_sub(layout.size(), Ordering::SeqCst);
+    }
+}
+
+#[global_allocator]
+static GLOBAL: ProbeAlloc = ProbeAlloc;
+
+#[derive(Debug, Diagnostic, Error)]
+#[error("leaf")]
+#[diagnostic(severity(Warning))]
+struct Leaf;
+
+#[derive(Debug, Error)]
+#[error("parent")]
+struct Parent {
+    leaf: Leaf,
+    count: usize,
+}
+
+impl Diagnostic for Parent {
+    fn related<'a>(&'a self) -> Option<Box<dyn Iterator<Item = &'a dyn Diagnostic> + 'a>> {
+        Some(Box::new(
+            std::iter::repeat(&self.leaf as &dyn Diagnostic).take(self.count),
+        ))
+    }
+}
+
+/// Discards everything written to it.
+struct Sink(usize);
+
+impl fmt::Write for Sink {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.0 += s.len();
+        Ok(())
+    }
+}
+
+/// Returns the extra memory used at peak while rendering `diag` with
+/// `render`, and the number of bytes written.
+fn peak_while_rendering(
+    diag: &Parent,
+    render: impl Fn(&mut Sink, &Parent) -> fmt::Result,
+) -> (usize, usize) {
+    let mut sink = Sink(0);
+    let before = LIVE.load(Ordering::SeqCst);
+    PEAK.store(before, Ordering::SeqCst);
+    render(&mut sink, diag).unwrap();
+    (PEAK.load(Ordering::SeqCst) - before, sink.0)
+}
+
+fn assert_flat(render: impl Fn(&mut Sink, &Parent) -> fmt::Result) {
+    let small = Parent {
+        leaf: Leaf,
+        count: 1_000,
+    };
+    let large = Parent {
+        leaf: Leaf,
+        count: 100_000,
+    };
+    let (small_peak, small_len) = peak_while_rendering(&small, &render);
+    let (large_peak, large_len) = peak_while_rendering(&large, &render);
+
+    assert!(large_len > small_len * 99);
+    // Collecting 100k `&dyn Diagnostic`s alone would take well over a
+    // megabyte, so leave a little slack for the test harness itself.
+    assert!(
+        large_peak <= small_peak + 4096,
+        "rendering 100k related diagnostics peaked at {} extra bytes, vs {} for 1k",
+        large_peak,
+        small_peak
+    );
+}
+
+// Both checks share one test so nothing else allocates while they run.
+#[test]
+#[cfg_attr(miri, ignore)]
+fn related_memory_is_flat() {
+    let handler = GraphicalReportHandler::new_themed(GraphicalTheme::unicode_nocolor());
+    assert_flat(|sink, diag| handler.render_report(sink, diag));
+    assert_flat(|sink, diag| handler.render_related_streaming(sink, diag.related().unwrap(), None));
+}
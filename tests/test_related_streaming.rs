@@ -0,0 +1,88 @@
+#![cfg(feature = "fancy-no-backtrace")]
+
+use std::cell::RefCell;
+use std::fmt;
+use std::rc::Rc;
+
+use miette::{Diagnostic, GraphicalReportHandler, GraphicalTheme};
+use thiserror::Error;
+
+#[derive(Debug, Diagnostic, Error)]
+#[error("leaf")]
+#[diagnostic(severity(Warning))]
+struct Leaf;
+
+/// Yields `count` leaves, panicking if asked for another one before every
+/// leaf yielded so far has been written to `out`.
+struct Checked<'a> {
+    leaf: &'a Leaf,
+    out: Rc<RefCell<String>>,
+    yielded: usize,
+    count: usize,
+}
+
+impl<'a> Iterator for Checked<'a> {
+    type Item = &'a dyn Diagnostic;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let rendered = self.out.borrow().matches("leaf").count();
+        assert_eq!(
+            rendered, self.yielded,
+            "related diagnostic requested before the previous one was rendered"
+        );
+        if self.yielded == self.count {
+            return None;
+        }
+        self.yielded += 1;
+        Some(self.leaf)
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("parent")]
+struct Parent {
+    leaf: Leaf,
+    out: Rc<RefCell<String>>,
+    count: usize,
+}
+
+impl Diagnostic for Parent {
+    fn related<'a>(&'a self) -> Option<Box<dyn Iterator<Item = &'a dyn Diagnostic> + 'a>> {
+        Some(Box::new(Checked {
+            leaf: &self.leaf,
+            out: self.out.clone(),
+            yielded: 0,
+            count: self.count,
+        }))
+    }
+}
+
+/// Appends everything written to it to a shared buffer.
+struct Shared(Rc<RefCell<String>>);
+
+impl fmt::Write for Shared {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.0.borrow_mut().push_str(s);
+        Ok(())
+    }
+}
+
+#[test]
+fn related_rendered_as_they_are_produced() {
+    let out = Rc::new(RefCell::new(String::new()));
+    let diag = Parent {
+        leaf: Leaf,
+        out: out.clone(),
+        count: 3,
+    };
+    GraphicalReportHandler::new_themed(GraphicalTheme::unicode_nocolor())
+        .render_report(&mut Shared(out.clone()), &diag)
+        .unwrap();
+    assert_eq!(out.borrow().matches("leaf").count(), 3);
+
+    out.borrow_mut().clear();
+    GraphicalReportHandler::new_themed(GraphicalTheme::unicode_nocolor())
+        .render_related_streaming(&mut Shared(out.clone()), diag.related().unwrap(), None)
+        .unwrap();
+    assert_eq!(out.borrow().matches("leaf").count(), 3);
+}
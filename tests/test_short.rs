@@ -0,0 +1,81 @@
+mod short_report_handler {
+    use miette::{Diagnostic, MietteError, NamedSource, Report, ShortReportHandler, SourceSpan};
+
+    use thiserror::Error;
+
+    fn fmt_report(handler: ShortReportHandler, diag: Report) -> String {
+        let mut out = String::new();
+        handler.render_report(&mut out, diag.as_ref()).unwrap();
+        out
+    }
+
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("oops!")]
+    #[diagnostic(help("try doing it better next time?"))]
+    struct MyBad {
+        #[source_code]
+        src: NamedSource<String>,
+        #[label("this bit here")]
+        highlight1: SourceSpan,
+        #[label("and here")]
+        highlight2: SourceSpan,
+        #[related]
+        related: Vec<Related>,
+    }
+
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("related oops!")]
+    #[diagnostic(code(oops::related), severity(Warning))]
+    struct Related {
+        #[label]
+        highlight: SourceSpan,
+    }
+
+    fn my_bad() -> MyBad {
+        MyBad {
+            src: NamedSource::new("bad_file.rs", "source\n  text\n    here".to_string()),
+            highlight1: (9, 4).into(),
+            highlight2: (18, 4).into(),
+            related: vec![Related {
+                highlight: (0, 6).into(),
+            }],
+        }
+    }
+
+    #[test]
+    fn one_line_per_diagnostic() -> Result<(), MietteError> {
+        let out = fmt_report(ShortReportHandler::new(), my_bad().into());
+        println!("Error: {}", out);
+        let expected = "bad_file.rs:2:3: error: oops!\n\
+                        bad_file.rs:1:1: warning[oops::related]: related oops!\n";
+        assert_eq!(expected, out);
+        Ok(())
+    }
+
+    #[test]
+    fn all_labels() -> Result<(), MietteError> {
+        let out = fmt_report(
+            ShortReportHandler::new().with_all_labels(true),
+            my_bad().into(),
+        );
+        println!("Error: {}", out);
+        let expected = "bad_file.rs:2:3: error: oops!\n\
+                        bad_file.rs:3:5: error: oops!\n\
+                        bad_file.rs:1:1: warning[oops::related]: related oops!\n";
+        assert_eq!(expected, out);
+        Ok(())
+    }
+
+    #[test]
+    fn no_labels() {
+        #[derive(Debug, Diagnostic, Error)]
+        #[error("oops!")]
+        #[diagnostic(code(oops::my::bad))]
+        struct MyBad;
+
+        assert_eq!(
+            "error[oops::my::bad]: oops!\n",
+            fmt_report(ShortReportHandler::new(), MyBad.into())
+        );
+    }
+}
@@ -0,0 +1,82 @@
+#![cfg(feature = "tracing")]
+
+use std::sync::{Arc, Mutex};
+
+use miette::{emit_tracing, MietteDiagnostic, Severity};
+use tracing::{
+    field::{Field, Visit},
+    span, Event, Level, Metadata, Subscriber,
+};
+
+#[derive(Debug, Default)]
+struct Recorded {
+    level: Option<Level>,
+    fields: Vec<(String, String)>,
+}
+
+struct Visitor<'a>(&'a mut Recorded);
+
+impl Visit for Visitor<'_> {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.0
+            .fields
+            .push((field.name().to_string(), format!("{:?}", value)));
+    }
+}
+
+/// Records every event it sees.
+#[derive(Clone, Default)]
+struct TestSubscriber(Arc<Mutex<Vec<Recorded>>>);
+
+impl Subscriber for TestSubscriber {
+    fn enabled(&self, _: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, _: &span::Attributes<'_>) -> span::Id {
+        span::Id::from_u64(1)
+    }
+
+    fn record(&self, _: &span::Id, _: &span::Record<'_>) {}
+
+    fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let mut recorded = Recorded {
+            level: Some(*event.metadata().level()),
+            ..Default::default()
+        };
+        event.record(&mut Visitor(&mut recorded));
+        self.0.lock().unwrap().push(recorded);
+    }
+
+    fn enter(&self, _: &span::Id) {}
+
+    fn exit(&self, _: &span::Id) {}
+}
+
+#[test]
+fn emits_warning_event() {
+    let subscriber = TestSubscriber::default();
+    let diag = MietteDiagnostic::new("deprecated option")
+        .with_code("config::deprecated")
+        .with_severity(Severity::Warning)
+        .with_label(miette::LabeledSpan::at(0..3, "here"));
+    tracing::subscriber::with_default(subscriber.clone(), || emit_tracing(&diag));
+
+    let events = subscriber.0.lock().unwrap();
+    assert_eq!(events.len(), 1);
+    let event = &events[0];
+    assert_eq!(event.level, Some(Level::WARN));
+    let field = |name: &str| {
+        event
+            .fields
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    };
+    assert_eq!(field("code"), Some("\"config::deprecated\""));
+    assert_eq!(field("url"), None);
+    assert_eq!(field("labels"), Some("1"));
+    assert_eq!(field("message"), Some("deprecated option"));
+}